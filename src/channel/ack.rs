@@ -0,0 +1,89 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// A handle to acknowledge records sent through a `FanOut`. Each consumer receives its own
+/// handle and calls `done` once the records are delivered or dead-lettered. Dropping a handle
+/// without calling `done`, like when the consumer is aborted, marks the records as lost.
+#[derive(Debug)]
+pub struct Ack {
+    state: Arc<AckState>,
+    done: bool,
+}
+
+/// Whether every handle of the records has been acknowledged.
+#[derive(Debug)]
+pub struct AckWatcher {
+    state: Arc<AckState>,
+}
+
+#[derive(Debug, Default)]
+struct AckState {
+    lost: AtomicBool,
+}
+
+impl Ack {
+    pub fn new() -> (Self, AckWatcher) {
+        let state = Arc::new(AckState::default());
+        let watcher = AckWatcher {
+            state: Arc::clone(&state),
+        };
+        (Self { state, done: false }, watcher)
+    }
+
+    pub fn done(mut self) {
+        self.done = true;
+    }
+}
+
+impl Clone for Ack {
+    /// Get another handle which has to be acknowledged on its own.
+    fn clone(&self) -> Self {
+        Self {
+            state: Arc::clone(&self.state),
+            done: false,
+        }
+    }
+}
+
+impl Drop for Ack {
+    fn drop(&mut self) {
+        if !self.done {
+            self.state.lost.store(true, Ordering::SeqCst);
+        }
+    }
+}
+
+impl AckWatcher {
+    /// Every handle has been acknowledged.
+    pub fn is_acked(&self) -> bool {
+        Arc::strong_count(&self.state) == 1 && !self.is_lost()
+    }
+
+    /// Some handle has been dropped without being acknowledged.
+    pub fn is_lost(&self) -> bool {
+        self.state.lost.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_is_acked_when_every_handle_is_done() {
+        let (ack, watcher) = Ack::new();
+        let other = ack.clone();
+
+        ack.done();
+        assert!(!watcher.is_acked());
+        other.done();
+        assert!(watcher.is_acked());
+
+        let (ack, watcher) = Ack::new();
+        drop(ack);
+        assert!(watcher.is_lost());
+        assert!(!watcher.is_acked());
+    }
+}
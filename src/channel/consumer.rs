@@ -1,40 +1,63 @@
 use super::{
     event::{ReceiverHalf, TryRecvResult},
-    Records,
+    Ack, Records,
 };
 
+use anyhow::Result;
 use axum::async_trait;
-use tokio::sync::watch;
-use tracing::{error, info, warn};
+use tokio::{
+    sync::mpsc,
+    time::{sleep_until, Instant},
+};
+use tracing::{error, info};
 
 #[async_trait]
 pub trait Consumer: ReceiverHalf + Send + Sync {
     fn identifier(&self) -> &str;
 
     /// Get records receiver.
-    fn rx_records(&mut self) -> &mut watch::Receiver<Records>;
+    fn rx_records(&mut self) -> &mut mpsc::Receiver<(Records, Ack)>;
+
+    /// Consume dynamodb stream. The ack is done once the records are delivered or given up,
+    /// possibly on a later flush.
+    async fn consume(&self, records: Records, ack: Ack) -> ();
+
+    /// When the records held by the consumer should be flushed. `None` means the consumer
+    /// holds no records.
+    fn flush_deadline(&self) -> Option<Instant> {
+        None
+    }
 
-    /// Consume dynamodb stream
-    async fn consume(&self, records: Records) -> ();
+    /// Consume the records held by the consumer.
+    async fn flush(&self) {}
 
-    /// Start consuming.
-    async fn start_consuming(&mut self) {
+    /// Start consuming. It returns `Ok` when the consumer is stopped by an event or the records
+    /// channel is closed, and `Err` when it fails, so that the consumer can be restarted.
+    /// Before it stops, the buffered records are consumed and the held records are flushed.
+    async fn start_consuming(&mut self) -> Result<()> {
         loop {
-            match self.rx_records().changed().await {
-                Ok(_) => {
-                    let records = {
-                        let records = self.rx_records().borrow_and_update();
-                        (*records).clone()
-                    };
-                    self.consume(records).await;
+            let received = match self.flush_deadline() {
+                Some(deadline) => tokio::select! {
+                    received = self.rx_records().recv() => Some(received),
+                    _ = sleep_until(deadline) => None,
+                },
+                None => Some(self.rx_records().recv().await),
+            };
+
+            match received {
+                Some(Some((records, ack))) => {
+                    self.consume(records, ack).await;
                 }
-                Err(err) => {
-                    warn!(
-                        "Failed to detect watch channel changes. This means the sender of this channel has been dropped. Stop consuming: \"{}\".",
+                Some(None) => {
+                    info!(
+                        "The records channel has been closed. Stop consuming: \"{}\".",
                         self.identifier()
                     );
-                    warn!("{:#?}", err);
-                    return;
+                    self.flush().await;
+                    return Ok(());
+                }
+                None => {
+                    self.flush().await;
                 }
             }
 
@@ -45,7 +68,13 @@ pub trait Consumer: ReceiverHalf + Send + Sync {
                         "Received an event to stop consuming: \"{}\".",
                         self.identifier()
                     );
-                    return;
+
+                    // Consume records which have already been buffered before stopping.
+                    while let Ok((records, ack)) = self.rx_records().try_recv() {
+                        self.consume(records, ack).await;
+                    }
+                    self.flush().await;
+                    return Ok(());
                 }
                 TryRecvResult::Error(err) => {
                     error!(
@@ -53,9 +82,146 @@ pub trait Consumer: ReceiverHalf + Send + Sync {
                         self.identifier()
                     );
                     error!("{:#?}", err);
-                    return;
+                    return Err(err);
                 }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{channel::Event, dynamodb::types::Record};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    };
+    use tokio::{sync::oneshot, time::Duration};
+
+    struct CountingConsumer {
+        consumed: AtomicUsize,
+        rx_event: oneshot::Receiver<Event>,
+        rx_records: mpsc::Receiver<(Records, Ack)>,
+    }
+
+    impl ReceiverHalf for CountingConsumer {
+        fn rx_event(&mut self) -> &mut oneshot::Receiver<Event> {
+            &mut self.rx_event
+        }
+    }
+
+    #[async_trait]
+    impl Consumer for CountingConsumer {
+        fn identifier(&self) -> &str {
+            "counter"
+        }
+
+        fn rx_records(&mut self) -> &mut mpsc::Receiver<(Records, Ack)> {
+            &mut self.rx_records
+        }
+
+        async fn consume(&self, _records: Records, ack: Ack) {
+            self.consumed.fetch_add(1, Ordering::SeqCst);
+            ack.done();
+        }
+    }
+
+    #[tokio::test]
+    async fn it_consumes_buffered_records_before_stopping() {
+        let (tx_event, rx_event) = oneshot::channel();
+        let (tx_records, rx_records) = mpsc::channel(3);
+        let mut consumer = CountingConsumer {
+            consumed: AtomicUsize::new(0),
+            rx_event,
+            rx_records,
+        };
+
+        let mut watchers = vec![];
+        for id in ["0", "1", "2"] {
+            let (ack, watcher) = Ack::new();
+            tx_records
+                .send((Records::from(vec![Record::new(id)]), ack))
+                .await
+                .unwrap();
+            watchers.push(watcher);
+        }
+        tx_event.send(Event::Close).unwrap();
+
+        assert!(consumer.start_consuming().await.is_ok());
+        assert_eq!(consumer.consumed.load(Ordering::SeqCst), 3);
+        assert!(watchers.iter().all(|watcher| watcher.is_acked()));
+    }
+
+    /// Holds every records until the deadline.
+    struct HoldingConsumer {
+        held: Mutex<Option<Instant>>,
+        flushed: AtomicUsize,
+        rx_event: oneshot::Receiver<Event>,
+        rx_records: mpsc::Receiver<(Records, Ack)>,
+    }
+
+    impl ReceiverHalf for HoldingConsumer {
+        fn rx_event(&mut self) -> &mut oneshot::Receiver<Event> {
+            &mut self.rx_event
+        }
+    }
+
+    #[async_trait]
+    impl Consumer for HoldingConsumer {
+        fn identifier(&self) -> &str {
+            "holder"
+        }
+
+        fn rx_records(&mut self) -> &mut mpsc::Receiver<(Records, Ack)> {
+            &mut self.rx_records
+        }
+
+        async fn consume(&self, _records: Records, ack: Ack) {
+            ack.done();
+            let mut held = self.held.lock().unwrap();
+            held.get_or_insert_with(|| Instant::now() + Duration::from_millis(10));
+        }
+
+        fn flush_deadline(&self) -> Option<Instant> {
+            *self.held.lock().unwrap()
+        }
+
+        async fn flush(&self) {
+            if self.held.lock().unwrap().take().is_some() {
+                self.flushed.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn it_flushes_held_records_after_the_deadline() {
+        let (tx_event, rx_event) = oneshot::channel();
+        let (tx_records, rx_records) = mpsc::channel(1);
+        let mut consumer = HoldingConsumer {
+            held: Mutex::new(None),
+            flushed: AtomicUsize::new(0),
+            rx_event,
+            rx_records,
+        };
+
+        let task = tokio::spawn(async move {
+            consumer.start_consuming().await.unwrap();
+            consumer.flushed.load(Ordering::SeqCst)
+        });
+
+        tx_records
+            .send((Records::from(vec![Record::new("0")]), Ack::new().0))
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        tx_records
+            .send((Records::from(vec![Record::new("1")]), Ack::new().0))
+            .await
+            .unwrap();
+        tx_event.send(Event::Close).unwrap();
+
+        // One flush by the deadline and one before stopping.
+        assert_eq!(task.await.unwrap(), 2);
+    }
+}
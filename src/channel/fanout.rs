@@ -0,0 +1,198 @@
+use super::{Ack, Records};
+
+use serde::{Deserialize, Serialize};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex, MutexGuard,
+};
+use tokio::sync::mpsc::{
+    self,
+    error::{SendError, TrySendError},
+};
+use tracing::warn;
+
+/// What to do when a consumer's buffer is full.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum OverflowPolicy {
+    /// Wait until the consumer has room. This slows down the stream.
+    #[default]
+    Block,
+    /// Discard the new records for the consumer.
+    DropNewest,
+}
+
+#[derive(Debug, Clone)]
+struct Subscriber {
+    id: String,
+    tx: mpsc::Sender<(Records, Ack)>,
+    policy: OverflowPolicy,
+}
+
+/// A bounded fan-out delivering every records to each subscriber.
+#[derive(Debug, Clone, Default)]
+pub struct FanOut {
+    subscribers: Arc<Mutex<Vec<Subscriber>>>,
+    subscribed: Arc<AtomicBool>,
+}
+
+impl FanOut {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a subscriber whose buffer can hold `capacity` batches of records at most.
+    pub fn subscribe<T: Into<String>>(
+        &self,
+        id: T,
+        capacity: usize,
+        policy: OverflowPolicy,
+    ) -> mpsc::Receiver<(Records, Ack)> {
+        let (tx, rx) = mpsc::channel::<(Records, Ack)>(capacity.max(1));
+
+        self.lock().push(Subscriber {
+            id: id.into(),
+            tx,
+            policy,
+        });
+        self.subscribed.store(true, Ordering::SeqCst);
+
+        rx
+    }
+
+    /// Remove the subscriber. Its receiver gets `None` after consuming the buffered records.
+    pub fn unsubscribe(&self, id: &str) {
+        self.lock().retain(|s| s.id != id);
+    }
+
+    /// Whether every subscriber has gone after any subscribed.
+    pub fn is_closed(&self) -> bool {
+        self.subscribed.load(Ordering::SeqCst) && self.lock().is_empty()
+    }
+
+    #[cfg(test)]
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Send records to every subscriber following their overflow policies. Each subscriber gets
+    /// its own handle of the ack. Records dropped by the policy or for closed subscribers are
+    /// acknowledged here as nobody is going to deliver them.
+    pub async fn send(&self, records: Records, ack: Ack) {
+        if records.is_empty() {
+            ack.done();
+            return;
+        }
+
+        let subscribers = self.lock().clone();
+
+        for Subscriber { id, tx, policy } in subscribers {
+            match policy {
+                OverflowPolicy::Block => {
+                    // The error means the receiver has been dropped. It is removed below.
+                    if let Err(SendError((_, ack))) = tx.send((records.clone(), ack.clone())).await
+                    {
+                        ack.done();
+                    }
+                }
+                OverflowPolicy::DropNewest => match tx.try_send((records.clone(), ack.clone())) {
+                    Ok(_) => {}
+                    Err(TrySendError::Full((_, ack))) => {
+                        warn!("The buffer of \"{id}\" is full. Drop records for it.");
+                        ack.done();
+                    }
+                    Err(TrySendError::Closed((_, ack))) => ack.done(),
+                },
+            }
+        }
+
+        ack.done();
+        self.lock().retain(|s| !s.tx.is_closed());
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<Subscriber>> {
+        self.subscribers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{channel::AckWatcher, dynamodb::types::Record};
+
+    fn records(event_id: &str) -> Records {
+        Records::from(vec![Record::new(event_id)])
+    }
+
+    async fn send(fanout: &FanOut, records: Records) -> AckWatcher {
+        let (ack, watcher) = Ack::new();
+        fanout.send(records, ack).await;
+        watcher
+    }
+
+    #[tokio::test]
+    async fn it_delivers_every_records_to_each_subscriber() {
+        let fanout = FanOut::new();
+        let mut rx0 = fanout.subscribe("0", 2, OverflowPolicy::Block);
+        let mut rx1 = fanout.subscribe("1", 2, OverflowPolicy::Block);
+
+        let a = send(&fanout, records("a")).await;
+        assert!(send(&fanout, Records::new()).await.is_acked());
+        let b = send(&fanout, records("b")).await;
+
+        for rx in [&mut rx0, &mut rx1] {
+            assert!(!a.is_acked());
+
+            let (records, ack) = rx.recv().await.unwrap();
+            assert!(records.includes("a"));
+            ack.done();
+
+            let (records, ack) = rx.recv().await.unwrap();
+            assert!(records.includes("b"));
+            ack.done();
+
+            assert!(rx.try_recv().is_err());
+        }
+
+        assert!(a.is_acked());
+        assert!(b.is_acked());
+    }
+
+    #[tokio::test]
+    async fn it_drops_newest_records_when_the_buffer_is_full() {
+        let fanout = FanOut::new();
+        let mut rx = fanout.subscribe("0", 1, OverflowPolicy::DropNewest);
+
+        let a = send(&fanout, records("a")).await;
+        let b = send(&fanout, records("b")).await;
+
+        assert!(!a.is_acked());
+        assert!(b.is_acked());
+
+        let (records, ack) = rx.recv().await.unwrap();
+        assert!(records.includes("a"));
+        assert!(rx.try_recv().is_err());
+
+        drop(ack);
+        assert!(a.is_lost());
+    }
+
+    #[tokio::test]
+    async fn it_removes_closed_subscribers() {
+        let fanout = FanOut::new();
+        let rx = fanout.subscribe("0", 1, OverflowPolicy::Block);
+        assert!(!fanout.is_closed());
+        let _rx = fanout.subscribe("1", 1, OverflowPolicy::Block);
+        assert_eq!(fanout.len(), 2);
+
+        drop(rx);
+        send(&fanout, records("a")).await;
+        assert_eq!(fanout.len(), 1);
+
+        fanout.unsubscribe("1");
+        assert_eq!(fanout.len(), 0);
+        assert!(fanout.is_closed());
+    }
+}
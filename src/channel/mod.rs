@@ -1,9 +1,15 @@
+mod ack;
 mod consumer;
 mod event;
+mod fanout;
 mod stream;
+mod supervisor;
 
 use super::dynamodb::types::Records;
 
+pub use ack::{Ack, AckWatcher};
 pub use consumer::Consumer;
 pub use event::{Event, ReceiverHalf, SenderHalf};
+pub use fanout::{FanOut, OverflowPolicy};
 pub use stream::Stream;
+pub use supervisor::{RestartConfig, Supervisor, TaskState};
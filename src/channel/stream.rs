@@ -1,14 +1,11 @@
 use super::{
     event::{ReceiverHalf, TryRecvResult},
-    Records,
+    Ack, FanOut, Records,
 };
 
 use anyhow::Result;
 use axum::async_trait;
-use tokio::{
-    sync::watch,
-    time::{sleep, Duration},
-};
+use tokio::time::{sleep, Duration};
 use tracing::{error, info};
 
 /// A stream should have one opponent and communicate each other.
@@ -17,32 +14,57 @@ pub trait Stream: ReceiverHalf + Send + Sync {
     fn table_name(&self) -> &str;
 
     /// Get records sender.
-    fn tx_records(&self) -> &watch::Sender<Records>;
+    fn tx_records(&self) -> &FanOut;
 
-    async fn iterate(&mut self) -> Result<Records>;
+    /// Get records with the ack to send them with. Watch the ack to know when every consumer
+    /// has delivered the records.
+    async fn iterate(&mut self) -> Result<(Records, Ack)>;
 
     /// You can overwrite this method to implement initialization before iterating.
     async fn init(&mut self) -> Result<()> {
         Ok(())
     }
 
-    /// Start streaming.
-    async fn start_streaming(&mut self, interval: Option<u64>) {
+    /// You can overwrite this method to persist progress of the records acknowledged by every
+    /// consumer.
+    async fn commit(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// You can overwrite this method to wait for the records sent to be acknowledged and persist
+    /// progress before the stream stops.
+    async fn close(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Start streaming. It returns `Ok` when the stream is stopped by an event and `Err` when it
+    /// fails, so that the stream can be restarted.
+    async fn start_streaming(&mut self, interval: Option<u64>) -> Result<()> {
         if let Err(err) = self.init().await {
             error!("Failed to initialize stream. Skip starting streaming: {err}");
             error!("{:#?}", err);
-            return;
+            return Err(err);
         }
 
         loop {
             match self.iterate().await {
-                Ok(records) => {
-                    if self.tx_records().send(records).is_err() {
+                Ok((records, ack)) => {
+                    self.tx_records().send(records, ack).await;
+
+                    if let Err(err) = self.commit().await {
+                        error!(
+                            "Failed to commit progress of \"{}\" table: {err}",
+                            self.table_name()
+                        );
+                        error!("{:#?}", err);
+                    }
+
+                    if self.tx_records().is_closed() {
                         info!(
                             "All record receivers are gone. Stop streaming from \"{}\" table.",
                             self.table_name()
                         );
-                        return;
+                        return Ok(());
                     }
                 }
                 Err(err) => {
@@ -50,7 +72,7 @@ pub trait Stream: ReceiverHalf + Send + Sync {
                         "Failed to iterate. Stop streaming from \"{}\" table: {err}",
                         self.table_name()
                     );
-                    return;
+                    return Err(err);
                 }
             }
 
@@ -61,14 +83,22 @@ pub trait Stream: ReceiverHalf + Send + Sync {
                         "Received an event to stop streaming. Stop streaming from \"{}\" table.",
                         self.table_name()
                     );
-                    return;
+
+                    if let Err(err) = self.close().await {
+                        error!(
+                            "Failed to commit progress of \"{}\" table: {err}",
+                            self.table_name()
+                        );
+                        error!("{:#?}", err);
+                    }
+                    return Ok(());
                 }
                 TryRecvResult::Error(err) => {
                     error!(
                         "Failed to receive events. Stop streaming from \"{}\" table: {err}",
                         self.table_name()
                     );
-                    return;
+                    return Err(err);
                 }
             }
 
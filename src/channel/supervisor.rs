@@ -0,0 +1,296 @@
+use super::{
+    event::{ReceiverHalf, TryRecvResult},
+    Consumer, Stream,
+};
+
+use serde::{Deserialize, Serialize};
+use std::{
+    sync::{Arc, Mutex, MutexGuard},
+    time::Instant,
+};
+use tokio::time::{sleep, Duration};
+use tracing::{error, info, warn};
+
+/// The restart options for failed streams and consumers.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct RestartConfig {
+    /// The maximum number of consecutive restarts. After that, the task is marked as failed.
+    pub max_restarts: u32,
+    /// The delay before the first restart. It doubles on every consecutive restart.
+    pub initial_interval_ms: u64,
+    /// The maximum delay between restarts. A task running longer than this is considered
+    /// recovered and its consecutive restarts are reset.
+    pub max_interval_ms: u64,
+}
+
+impl Default for RestartConfig {
+    fn default() -> Self {
+        Self {
+            max_restarts: 10,
+            initial_interval_ms: 1_000,
+            max_interval_ms: 60_000,
+        }
+    }
+}
+
+impl RestartConfig {
+    fn backoff(&self, failures: u32) -> Duration {
+        let delay = self
+            .initial_interval_ms
+            .saturating_mul(2u64.saturating_pow(failures.saturating_sub(1)))
+            .min(self.max_interval_ms);
+        Duration::from_millis(delay)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TaskState {
+    #[default]
+    Running,
+    /// Waiting for the next restart after a failure.
+    Restarting,
+    /// Stopped by an event.
+    Stopped,
+    /// Gave up restarting. The task never runs again.
+    Failed,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct TaskStatus {
+    pub state: TaskState,
+    /// The total number of restarts.
+    pub restarts: u32,
+    pub last_error: Option<String>,
+}
+
+/// Run a stream or a consumer and restart it with exponential backoff when it fails.
+/// Clones share the status.
+#[derive(Debug, Clone, Default)]
+pub struct Supervisor {
+    config: RestartConfig,
+    status: Arc<Mutex<TaskStatus>>,
+}
+
+impl Supervisor {
+    pub fn new(config: RestartConfig) -> Self {
+        Self {
+            config,
+            status: Arc::default(),
+        }
+    }
+
+    pub fn status(&self) -> TaskStatus {
+        self.lock().clone()
+    }
+
+    pub async fn supervise_stream<S: Stream + ?Sized>(
+        &self,
+        stream: &mut S,
+        interval: Option<u64>,
+    ) {
+        let name = format!("stream of \"{}\" table", stream.table_name());
+        let mut failures: u32 = 0;
+
+        loop {
+            self.set_state(TaskState::Running);
+            let started_at = Instant::now();
+
+            match stream.start_streaming(interval).await {
+                Ok(()) => break,
+                Err(err) => {
+                    failures = self.failures(failures, started_at);
+                    if !self.restart(stream, &name, failures, err).await {
+                        return;
+                    }
+                }
+            }
+        }
+
+        self.set_state(TaskState::Stopped);
+    }
+
+    pub async fn supervise_consumer<C: Consumer + ?Sized>(&self, consumer: &mut C) {
+        let name = format!("consumer \"{}\"", consumer.identifier());
+        let mut failures: u32 = 0;
+
+        loop {
+            self.set_state(TaskState::Running);
+            let started_at = Instant::now();
+
+            match consumer.start_consuming().await {
+                Ok(()) => break,
+                Err(err) => {
+                    failures = self.failures(failures, started_at);
+                    if !self.restart(consumer, &name, failures, err).await {
+                        return;
+                    }
+                }
+            }
+        }
+
+        self.set_state(TaskState::Stopped);
+    }
+
+    /// Count consecutive failures. A task which ran long enough starts counting again.
+    fn failures(&self, failures: u32, started_at: Instant) -> u32 {
+        if started_at.elapsed() >= Duration::from_millis(self.config.max_interval_ms) {
+            1
+        } else {
+            failures + 1
+        }
+    }
+
+    /// Wait before restarting the failed task. Returns false if the task should not run again.
+    async fn restart<R: ReceiverHalf + Send + ?Sized>(
+        &self,
+        target: &mut R,
+        name: &str,
+        failures: u32,
+        err: anyhow::Error,
+    ) -> bool {
+        {
+            let mut status = self.lock();
+            status.last_error = Some(format!("{err:#}"));
+
+            if failures > self.config.max_restarts {
+                status.state = TaskState::Failed;
+                error!(
+                    "The {name} failed {} times in a row. Give up restarting it.",
+                    failures
+                );
+                return false;
+            }
+
+            status.state = TaskState::Restarting;
+        }
+
+        if is_stopped(target) {
+            self.set_state(TaskState::Stopped);
+            return false;
+        }
+
+        let delay = self.config.backoff(failures);
+        warn!("The {name} failed. Restart it in {}ms.", delay.as_millis());
+        sleep(delay).await;
+
+        if is_stopped(target) {
+            self.set_state(TaskState::Stopped);
+            return false;
+        }
+
+        self.lock().restarts += 1;
+        info!("Restart the {name}.");
+        true
+    }
+
+    fn set_state(&self, state: TaskState) {
+        self.lock().state = state;
+    }
+
+    fn lock(&self) -> MutexGuard<'_, TaskStatus> {
+        self.status
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Check if the opponent has asked the task to stop or has gone away.
+fn is_stopped<R: ReceiverHalf + ?Sized>(target: &mut R) -> bool {
+    !matches!(target.try_recv_event(), TryRecvResult::Empty)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        channel::{Ack, Event, FanOut},
+        dynamodb::types::Records,
+    };
+    use anyhow::Result;
+    use axum::async_trait;
+    use tokio::sync::oneshot;
+
+    struct FailingStream {
+        init_failures: u32,
+        rx_event: oneshot::Receiver<Event>,
+        tx_records: FanOut,
+    }
+
+    impl ReceiverHalf for FailingStream {
+        fn rx_event(&mut self) -> &mut oneshot::Receiver<Event> {
+            &mut self.rx_event
+        }
+    }
+
+    #[async_trait]
+    impl Stream for FailingStream {
+        fn table_name(&self) -> &str {
+            "People"
+        }
+
+        fn tx_records(&self) -> &FanOut {
+            &self.tx_records
+        }
+
+        async fn init(&mut self) -> Result<()> {
+            if self.init_failures > 0 {
+                self.init_failures -= 1;
+                anyhow::bail!("The table doesn't exist yet");
+            }
+            Ok(())
+        }
+
+        async fn iterate(&mut self) -> Result<(Records, Ack)> {
+            anyhow::bail!("Failed to describe stream")
+        }
+    }
+
+    fn config(max_restarts: u32) -> RestartConfig {
+        RestartConfig {
+            max_restarts,
+            initial_interval_ms: 1,
+            max_interval_ms: 60_000,
+        }
+    }
+
+    #[tokio::test]
+    async fn it_gives_up_restarting_after_the_limit() {
+        let (_tx, rx) = oneshot::channel();
+        let mut stream = FailingStream {
+            init_failures: 2,
+            rx_event: rx,
+            tx_records: FanOut::new(),
+        };
+
+        let supervisor = Supervisor::new(config(3));
+        supervisor.supervise_stream(&mut stream, None).await;
+
+        let status = supervisor.status();
+        assert_eq!(status.state, TaskState::Failed);
+        assert_eq!(status.restarts, 3);
+        assert_eq!(
+            status.last_error.as_deref(),
+            Some("Failed to describe stream")
+        );
+    }
+
+    #[tokio::test]
+    async fn it_stops_restarting_when_closed() {
+        let (tx, rx) = oneshot::channel();
+        let mut stream = FailingStream {
+            init_failures: 10,
+            rx_event: rx,
+            tx_records: FanOut::new(),
+        };
+        tx.send(Event::Close).unwrap();
+
+        let supervisor = Supervisor::new(config(3));
+        supervisor.supervise_stream(&mut stream, None).await;
+
+        let status = supervisor.status();
+        assert_eq!(status.state, TaskState::Stopped);
+        assert_eq!(status.restarts, 0);
+    }
+}
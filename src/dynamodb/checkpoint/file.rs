@@ -0,0 +1,123 @@
+use super::{Checkpoint, CheckpointStore};
+
+use anyhow::Result;
+use axum::async_trait;
+use std::{
+    collections::HashMap,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+use tokio::{fs, sync::Mutex};
+
+type Tables = HashMap<String, HashMap<String, String>>;
+
+/// A checkpoint store persisting checkpoints as a JSON file like the following.
+///
+/// ```json
+/// { "People": { "shardId-00000001": "000000000000000000001" } }
+/// ```
+#[derive(Debug)]
+pub struct FileCheckpointStore {
+    path: PathBuf,
+    tables: Mutex<Tables>,
+}
+
+impl FileCheckpointStore {
+    /// Open the checkpoint file. A missing file is treated as an empty one.
+    pub async fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+
+        let tables: Tables = match fs::read_to_string(&path).await {
+            Ok(content) => serde_json::from_str(&content)?,
+            Err(err) if err.kind() == ErrorKind::NotFound => HashMap::new(),
+            Err(err) => return Err(anyhow::Error::from(err)),
+        };
+
+        Ok(Self {
+            path,
+            tables: Mutex::new(tables),
+        })
+    }
+}
+
+#[async_trait]
+impl CheckpointStore for FileCheckpointStore {
+    async fn load(&self, table: &str, shard_id: &str) -> Result<Option<String>> {
+        let tables = self.tables.lock().await;
+        Ok(tables
+            .get(table)
+            .and_then(|shards| shards.get(shard_id))
+            .cloned())
+    }
+
+    async fn save(&self, table: &str, checkpoints: Vec<Checkpoint>) -> Result<()> {
+        if checkpoints.is_empty() {
+            return Ok(());
+        }
+
+        // Hold the lock while writing so that concurrent saves don't interleave.
+        let mut tables = self.tables.lock().await;
+        let shards = tables.entry(table.to_string()).or_default();
+
+        for Checkpoint {
+            shard_id,
+            sequence_number,
+        } in checkpoints
+        {
+            shards.insert(shard_id, sequence_number);
+        }
+
+        // Write to a temporary file first and rename it not to leave a broken file. The suffix
+        // is added to the whole file name so that files sharing a stem don't share it.
+        let content = serde_json::to_vec(&*tables)?;
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        fs::write(&tmp, content).await?;
+        fs::rename(&tmp, &self.path).await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ulid::Ulid;
+
+    #[tokio::test]
+    async fn it_saves_and_loads_checkpoints() {
+        let path = std::env::temp_dir().join(format!("{}.json", Ulid::new()));
+
+        let store = FileCheckpointStore::open(&path).await.unwrap();
+        assert_eq!(store.load("People", "shard-0").await.unwrap(), None);
+
+        store
+            .save(
+                "People",
+                vec![
+                    Checkpoint::new("shard-0", "100"),
+                    Checkpoint::new("shard-1", "200"),
+                ],
+            )
+            .await
+            .unwrap();
+        store
+            .save("People", vec![Checkpoint::new("shard-0", "300")])
+            .await
+            .unwrap();
+
+        // Reopen the file to confirm that the checkpoints are persisted.
+        let store = FileCheckpointStore::open(&path).await.unwrap();
+        assert_eq!(
+            store.load("People", "shard-0").await.unwrap(),
+            Some("300".into())
+        );
+        assert_eq!(
+            store.load("People", "shard-1").await.unwrap(),
+            Some("200".into())
+        );
+        assert_eq!(store.load("User", "shard-0").await.unwrap(), None);
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,32 @@
+mod file;
+
+use anyhow::Result;
+use axum::async_trait;
+use serde::{Deserialize, Serialize};
+
+pub use file::FileCheckpointStore;
+
+/// The last delivered sequence number of a shard.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub shard_id: String,
+    pub sequence_number: String,
+}
+
+impl Checkpoint {
+    pub fn new<S: Into<String>, T: Into<String>>(shard_id: S, sequence_number: T) -> Self {
+        Self {
+            shard_id: shard_id.into(),
+            sequence_number: sequence_number.into(),
+        }
+    }
+}
+
+#[async_trait]
+pub trait CheckpointStore: Send + Sync {
+    /// Load the last delivered sequence number of the shard in the table's subscription.
+    async fn load(&self, table: &str, shard_id: &str) -> Result<Option<String>>;
+
+    /// Save checkpoints of the table's subscription.
+    async fn save(&self, table: &str, checkpoints: Vec<Checkpoint>) -> Result<()>;
+}
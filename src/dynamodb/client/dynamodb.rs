@@ -1,6 +1,6 @@
 use super::{
     Client, GetIteratorOutput, GetRecordsOutput, GetShardsOutput, GetStreamArnOutput, Record,
-    Records, Shard,
+    Records, Shard, ShardIteratorType, TrimmedDataError,
 };
 
 use anyhow::Result;
@@ -9,7 +9,7 @@ use aws_sdk_dynamodbstreams::{
     config::Builder as StreamConfigBuilder,
     error::SdkError,
     operation::{get_records::GetRecordsError, get_shard_iterator::GetShardIteratorError},
-    types::{ShardIteratorType, StreamDescription},
+    types::StreamDescription,
     Client as StreamClient,
 };
 use axum::async_trait;
@@ -75,12 +75,19 @@ impl DynamodbClientBuilder {
 
 #[async_trait]
 impl Client for DynamodbClient {
-    async fn get_iterator(&self, stream_arn: &str, shard_id: &str) -> Result<GetIteratorOutput> {
+    async fn get_iterator(
+        &self,
+        stream_arn: &str,
+        shard_id: &str,
+        iterator_type: ShardIteratorType,
+        sequence_number: Option<&str>,
+    ) -> Result<GetIteratorOutput> {
         self.stream_client
             .get_shard_iterator()
             .stream_arn(stream_arn)
             .shard_id(shard_id)
-            .shard_iterator_type(ShardIteratorType::Latest)
+            .shard_iterator_type(iterator_type.into())
+            .set_sequence_number(sequence_number.map(String::from))
             .send()
             .await
             .map(|output| GetIteratorOutput {
@@ -178,12 +185,16 @@ fn from_get_iterator_err(err: SdkError<GetShardIteratorError>) -> Result<GetIter
         SdkError::ServiceError(e) => {
             let e = e.into_err();
             match e {
-                // Close shard if response is either ResourceNotFound or TrimmedDataAccess
-                ResourceNotFoundException(_) | TrimmedDataAccessException(_) => {
+                // Close shard if response is ResourceNotFound. The shard is still open on
+                // TrimmedDataAccess, so that it is read again from another position.
+                ResourceNotFoundException(_) => {
                     warn!("GetShardIterator operation failed due to {e}");
                     warn!("{:#?}", e);
                     Ok(GetIteratorOutput { iterator: None })
                 }
+                TrimmedDataAccessException(_) => {
+                    Err(anyhow::Error::from(e).context(TrimmedDataError))
+                }
                 _ => Err(anyhow::Error::from(e)),
             }
         }
@@ -198,12 +209,10 @@ fn from_get_records_err(err: SdkError<GetRecordsError>) -> Result<GetRecordsOutp
         SdkError::ServiceError(e) => {
             let e = e.into_err();
             match e {
-                // Close shard if response is one of ExpiredIterator, LimitExceeded
-                // ResourceNotFound and TrimmedDataAccess.
-                ExpiredIteratorException(_)
-                | LimitExceededException(_)
-                | ResourceNotFoundException(_)
-                | TrimmedDataAccessException(_) => {
+                // Close shard if response is ResourceNotFound. TrimmedDataAccess, ExpiredIterator
+                // and LimitExceeded are returned as errors so that the shard is read again with a
+                // new iterator.
+                ResourceNotFoundException(_) => {
                     warn!("GetRecords operation failed due to {e}");
                     warn!("{:#?}", e);
                     Ok(GetRecordsOutput {
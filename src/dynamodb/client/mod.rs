@@ -1,13 +1,20 @@
 mod dynamodb;
 
 use super::shard::Shard;
-use super::types::{Record, Records};
+use super::types::{Record, Records, ShardIteratorType};
 
 use anyhow::Result;
 use axum::async_trait;
+use thiserror::Error;
 
 pub use dynamodb::DynamodbClient;
 
+/// The position of a shard iterator is older than the retention period of the stream, like a
+/// checkpoint saved more than 24 hours ago.
+#[derive(Debug, Error)]
+#[error("The position of the shard iterator has been trimmed")]
+pub struct TrimmedDataError;
+
 #[derive(Debug)]
 pub struct GetIteratorOutput {
     pub iterator: Option<String>,
@@ -32,7 +39,13 @@ pub struct GetStreamArnOutput {
 
 #[async_trait]
 pub trait Client: Send + Sync {
-    async fn get_iterator(&self, stream_arn: &str, shard_id: &str) -> Result<GetIteratorOutput>;
+    async fn get_iterator(
+        &self,
+        stream_arn: &str,
+        shard_id: &str,
+        iterator_type: ShardIteratorType,
+        sequence_number: Option<&str>,
+    ) -> Result<GetIteratorOutput>;
     async fn get_records(&self, iterator: &str) -> Result<GetRecordsOutput>;
     async fn get_shards(
         &self,
@@ -0,0 +1,92 @@
+use super::{DedupStore, SeenEvent};
+
+use anyhow::Result;
+use axum::async_trait;
+use std::{
+    collections::HashMap,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+use tokio::{fs, sync::Mutex};
+
+type Tables = HashMap<String, Vec<SeenEvent>>;
+
+/// A dedup store persisting event IDs as a JSON file like the following.
+///
+/// ```json
+/// { "People": [{ "eventId": "c4ca4238a0b9...", "seenAt": "2023-11-20T00:00:00Z" }] }
+/// ```
+#[derive(Debug)]
+pub struct FileDedupStore {
+    path: PathBuf,
+    tables: Mutex<Tables>,
+}
+
+impl FileDedupStore {
+    /// Open the dedup file. A missing file is treated as an empty one.
+    pub async fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+
+        let tables: Tables = match fs::read_to_string(&path).await {
+            Ok(content) => serde_json::from_str(&content)?,
+            Err(err) if err.kind() == ErrorKind::NotFound => HashMap::new(),
+            Err(err) => return Err(anyhow::Error::from(err)),
+        };
+
+        Ok(Self {
+            path,
+            tables: Mutex::new(tables),
+        })
+    }
+}
+
+#[async_trait]
+impl DedupStore for FileDedupStore {
+    async fn load(&self, table: &str) -> Result<Vec<SeenEvent>> {
+        let tables = self.tables.lock().await;
+        Ok(tables.get(table).cloned().unwrap_or_default())
+    }
+
+    async fn save(&self, table: &str, events: Vec<SeenEvent>) -> Result<()> {
+        // Hold the lock while writing so that concurrent saves don't interleave.
+        let mut tables = self.tables.lock().await;
+        tables.insert(table.to_string(), events);
+
+        // Write to a temporary file first and rename it not to leave a broken file. The suffix
+        // is added to the whole file name so that files sharing a stem don't share it.
+        let content = serde_json::to_vec(&*tables)?;
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        fs::write(&tmp, content).await?;
+        fs::rename(&tmp, &self.path).await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use ulid::Ulid;
+
+    #[tokio::test]
+    async fn it_saves_and_loads_seen_events() {
+        let path = std::env::temp_dir().join(format!("{}.json", Ulid::new()));
+        let event = SeenEvent {
+            event_id: "0".into(),
+            seen_at: Utc::now(),
+        };
+
+        let store = FileDedupStore::open(&path).await.unwrap();
+        assert!(store.load("People").await.unwrap().is_empty());
+        store.save("People", vec![event.clone()]).await.unwrap();
+
+        // Reopen the file to confirm that the events are persisted.
+        let store = FileDedupStore::open(&path).await.unwrap();
+        assert_eq!(store.load("People").await.unwrap(), vec![event]);
+        assert!(store.load("User").await.unwrap().is_empty());
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,225 @@
+mod file;
+
+use super::types::Records;
+
+use anyhow::Result;
+use axum::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashSet, VecDeque},
+    time::Duration,
+};
+
+pub use file::FileDedupStore;
+
+const DEFAULT_CAPACITY: usize = 100_000;
+
+/// The options of deduplication by `eventID`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DedupConfig {
+    /// The maximum number of event IDs to remember.
+    pub capacity: usize,
+    /// How long to remember an event ID.
+    pub window: Duration,
+}
+
+impl DedupConfig {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            capacity: DEFAULT_CAPACITY,
+            window,
+        }
+    }
+}
+
+/// An event ID which has been delivered.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SeenEvent {
+    pub event_id: String,
+    pub seen_at: DateTime<Utc>,
+}
+
+/// Remember event IDs of the records delivered recently to drop the records read again.
+/// The oldest ones are forgotten when they are out of the window or over the capacity.
+#[derive(Debug)]
+pub struct Deduplicator {
+    config: DedupConfig,
+    ids: HashSet<String>,
+    events: VecDeque<SeenEvent>,
+    /// The IDs of the records sent but not acknowledged yet. They are remembered once delivered.
+    in_flight: HashSet<String>,
+    changed: bool,
+}
+
+impl Deduplicator {
+    pub fn new(config: DedupConfig) -> Self {
+        Self {
+            config,
+            ids: HashSet::new(),
+            events: VecDeque::new(),
+            in_flight: HashSet::new(),
+            changed: false,
+        }
+    }
+
+    /// Remember the events restored from a store.
+    pub fn restore(&mut self, events: Vec<SeenEvent>) {
+        for event in events {
+            self.remember(event);
+        }
+        self.forget(Utc::now());
+        self.changed = false;
+    }
+
+    /// Drop the records whose event ID has been delivered or is being delivered. Returns the
+    /// number of dropped records.
+    pub fn dedup(&mut self, records: &mut Records) -> usize {
+        self.forget(Utc::now());
+
+        let before = records.len();
+        records.retain(|record| match record.event_id() {
+            Some(id) if self.ids.contains(id) => false,
+            Some(id) => self.in_flight.insert(id.to_string()),
+            None => true,
+        });
+        before - records.len()
+    }
+
+    /// Remember the events whose records have been delivered.
+    pub fn acknowledge(&mut self, event_ids: Vec<String>) {
+        let now = Utc::now();
+        for event_id in event_ids {
+            self.in_flight.remove(&event_id);
+            self.remember(SeenEvent {
+                event_id,
+                seen_at: now,
+            });
+            self.changed = true;
+        }
+    }
+
+    /// Forget the records being delivered so that they are delivered when read again.
+    pub fn release(&mut self, event_ids: &[String]) {
+        for event_id in event_ids {
+            self.in_flight.remove(event_id);
+        }
+    }
+
+    /// Get all the remembered events if they have changed since the last call.
+    pub fn take_changes(&mut self) -> Option<Vec<SeenEvent>> {
+        if !self.changed {
+            return None;
+        }
+        self.changed = false;
+        Some(self.events.iter().cloned().collect())
+    }
+
+    fn remember(&mut self, event: SeenEvent) {
+        if self.ids.insert(event.event_id.clone()) {
+            self.events.push_back(event);
+        }
+
+        while self.events.len() > self.config.capacity {
+            self.pop();
+        }
+    }
+
+    fn forget(&mut self, now: DateTime<Utc>) {
+        let window =
+            chrono::Duration::from_std(self.config.window).unwrap_or(chrono::Duration::MAX);
+        while self
+            .events
+            .front()
+            .is_some_and(|event| now - event.seen_at > window)
+        {
+            self.pop();
+        }
+    }
+
+    fn pop(&mut self) {
+        if let Some(event) = self.events.pop_front() {
+            self.ids.remove(&event.event_id);
+            self.changed = true;
+        }
+    }
+}
+
+#[async_trait]
+pub trait DedupStore: Send + Sync {
+    /// Load the events remembered for the table's subscription.
+    async fn load(&self, table: &str) -> Result<Vec<SeenEvent>>;
+
+    /// Save the events remembered for the table's subscription.
+    async fn save(&self, table: &str, events: Vec<SeenEvent>) -> Result<()>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dynamodb::types::Record;
+
+    fn records(ids: &[&str]) -> Records {
+        Records::from(ids.iter().map(|id| Record::new(*id)).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn it_drops_records_seen_in_the_window() {
+        let mut dedup = Deduplicator::new(DedupConfig::new(Duration::from_secs(60)));
+
+        let mut first = records(&["0", "1", "1"]);
+        assert_eq!(dedup.dedup(&mut first), 1);
+        assert_eq!(first.len(), 2);
+
+        // "1" is being delivered.
+        let mut second = records(&["1", "2"]);
+        assert_eq!(dedup.dedup(&mut second), 1);
+        assert!(second.includes("2"));
+
+        // Nothing is remembered until the records are delivered.
+        assert!(dedup.take_changes().is_none());
+        dedup.acknowledge(vec!["0".into(), "1".into()]);
+        dedup.acknowledge(vec!["2".into()]);
+
+        let mut third = records(&["0", "2", "3"]);
+        assert_eq!(dedup.dedup(&mut third), 2);
+
+        let events = dedup.take_changes().unwrap();
+        assert_eq!(events.len(), 3);
+        assert!(dedup.take_changes().is_none());
+
+        // "3" has been lost before delivered.
+        dedup.release(&["3".into()]);
+        let mut fourth = records(&["3"]);
+        assert_eq!(dedup.dedup(&mut fourth), 0);
+    }
+
+    #[test]
+    fn it_forgets_events_out_of_the_window_or_over_the_capacity() {
+        let mut dedup = Deduplicator::new(DedupConfig {
+            capacity: 2,
+            window: Duration::from_secs(60),
+        });
+        dedup.restore(vec![
+            SeenEvent {
+                event_id: "old".into(),
+                seen_at: Utc::now() - chrono::Duration::seconds(120),
+            },
+            SeenEvent {
+                event_id: "0".into(),
+                seen_at: Utc::now(),
+            },
+        ]);
+
+        let mut first = records(&["old", "0", "1", "2"]);
+        assert_eq!(dedup.dedup(&mut first), 1);
+        assert_eq!(first.len(), 3);
+        dedup.acknowledge(vec!["old".into(), "1".into(), "2".into()]);
+
+        // "0" and "old" have been pushed out by "1" and "2".
+        let mut second = records(&["0", "2"]);
+        assert_eq!(dedup.dedup(&mut second), 1);
+        assert!(second.includes("0"));
+    }
+}
@@ -1,9 +1,4 @@
-use super::{client::Client, shard::Shard, types::Records};
-
-use async_recursion::async_recursion;
-use std::sync::Arc;
-use tokio::sync::mpsc::Sender;
-use tracing::error;
+use super::shard::Shard;
 
 #[derive(Debug, Clone)]
 pub struct Lineage {
@@ -68,29 +63,17 @@ impl Lineage {
         }
     }
 
-    #[async_recursion]
-    pub async fn get_records(self, client: Arc<dyn Client>, tx: Sender<(Option<Shard>, Records)>) {
+    /// Split into the oldest shard and its descendants.
+    pub fn into_parts(self) -> (Shard, Vec<Shard>) {
         let Lineage { shard, children } = self;
-
-        let result = shard
-            .get_records(Arc::clone(&client))
-            .await
-            .unwrap_or_else(|err| {
-                error!("Failed to get records from shard: {:#?}", err);
-                (None, Records::default())
-            });
-
-        if let Err(err) = tx.send(result).await {
-            error!("Failed to send get records result: {:#?}", err);
-        }
-
-        for child in children {
-            let client = Arc::clone(&client);
-            let tx = tx.clone();
-
-            tokio::spawn(async move {
-                child.get_records(client, tx).await;
-            });
-        }
+        let descendants = children
+            .into_iter()
+            .flat_map(|child| {
+                let (shard, mut descendants) = child.into_parts();
+                descendants.insert(0, shard);
+                descendants
+            })
+            .collect();
+        (shard, descendants)
     }
 }
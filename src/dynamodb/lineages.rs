@@ -1,7 +1,24 @@
-use super::{client::Client, lineage::Lineage, shard::Shard, types::Records};
+use super::{
+    checkpoint::Checkpoint, client::Client, lineage::Lineage, shard::Shard, types::Records,
+};
 
+use anyhow::Result;
 use std::sync::Arc;
 use tokio::sync::mpsc;
+use tracing::error;
+
+/// The result of reading records from lineages.
+#[derive(Debug, Default)]
+pub struct Polled {
+    pub records: Records,
+    /// The shards which are still open, including ones waiting for their parents.
+    pub shards: Vec<Shard>,
+    /// The IDs of the shards closed in this read.
+    pub closed: Vec<String>,
+    pub checkpoints: Vec<Checkpoint>,
+    /// Whether records have been read from any shard without failures.
+    pub read: bool,
+}
 
 #[derive(Debug)]
 pub struct Lineages {
@@ -10,34 +27,84 @@ pub struct Lineages {
 }
 
 impl Lineages {
-    pub async fn get_records(self, client: Arc<dyn Client>) -> (Records, Vec<Shard>) {
-        let mut records = Records::new();
-        let mut shards: Vec<Shard> = vec![];
-
-        let (tx, mut rx) = mpsc::channel::<(Option<Shard>, Records)>(self.shard_len);
+    /// Read records only from the oldest shard of each lineage. Its descendants wait until it is
+    /// closed and its records are delivered, so that records of a key are kept in order across
+    /// shard splits. Shard iterators are acquired only for the shards to read, since they expire
+    /// while the descendants wait.
+    pub async fn get_records(
+        self,
+        client: Arc<dyn Client>,
+        stream_arn: &str,
+        table: &str,
+    ) -> Polled {
+        let mut polled = Polled::default();
+
+        let (tx, mut rx) =
+            mpsc::channel::<(String, bool, Option<Shard>, Result<Records>)>(self.shard_len.max(1));
+        // The shards closed while the stream was reading them.
+        let mut parents: Vec<String> = vec![];
 
         for lineage in self.lineages {
+            let (shard, mut descendants) = lineage.into_parts();
+            polled.shards.append(&mut descendants);
+
             let client = Arc::clone(&client);
             let tx = tx.clone();
+            let arn = stream_arn.to_string();
+            let table = table.to_string();
 
             tokio::spawn(async move {
-                lineage.get_records(client, tx).await;
+                let shard_id = shard.id().to_string();
+                let unread = shard.is_unread_at_latest();
+                let (shard, records) = shard.get_records(client, &arn, &table).await;
+
+                if let Err(err) = tx.send((shard_id, unread, shard, records)).await {
+                    error!("Failed to send records: {err}");
+                    error!("{:#?}", err);
+                }
             });
         }
 
         drop(tx);
 
-        while let Some((_shard, mut _records)) = rx.recv().await {
-            if let Some(_shard) = _shard {
-                shards.push(_shard);
+        while let Some((shard_id, unread, shard, records)) = rx.recv().await {
+            match shard {
+                Some(shard) => polled.shards.push(shard),
+                None => {
+                    // A shard started at LATEST and closed without records on its first read had
+                    // been closed before the stream started, so its children start at LATEST too.
+                    if !(unread && records.as_ref().is_ok_and(Records::is_empty)) {
+                        parents.push(shard_id.clone());
+                    }
+                    polled.closed.push(shard_id.clone());
+                }
             }
 
-            if !_records.is_empty() {
-                records.append(&mut _records);
+            // The failure has been logged by the shard.
+            let Ok(mut records) = records else {
+                continue;
+            };
+            polled.read = true;
+
+            if let Some(sequence_number) = records.last_sequence_number() {
+                polled
+                    .checkpoints
+                    .push(Checkpoint::new(shard_id, sequence_number));
             }
+
+            polled.records.append(&mut records);
         }
 
-        (records, shards)
+        for shard in polled.shards.iter_mut() {
+            if shard
+                .parent()
+                .is_some_and(|parent| parents.iter().any(|p| p == parent))
+            {
+                shard.follow_parent();
+            }
+        }
+
+        polled
     }
 }
 
@@ -70,8 +137,120 @@ fn push_shard(lineages: Vec<Lineage>, shard: Shard) -> Vec<Lineage> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::dynamodb::{
+        client::{GetIteratorOutput, GetRecordsOutput, GetShardsOutput, GetStreamArnOutput},
+        types::{Record, ShardIteratorType},
+    };
+    use axum::async_trait;
     use itertools::Itertools;
 
+    /// Shard "0" has two pages of records and is closed after them. Shard "1" is its child.
+    /// Shard "2" has been closed without records. Reading other shards fails.
+    struct MockClient;
+
+    #[async_trait]
+    impl Client for MockClient {
+        async fn get_iterator(
+            &self,
+            _stream_arn: &str,
+            shard_id: &str,
+            _iterator_type: ShardIteratorType,
+            _sequence_number: Option<&str>,
+        ) -> Result<GetIteratorOutput> {
+            Ok(GetIteratorOutput {
+                iterator: Some(format!("{shard_id}-a")),
+            })
+        }
+
+        async fn get_records(&self, iterator: &str) -> Result<GetRecordsOutput> {
+            let (records, next_iterator) = match iterator {
+                "0-a" => (vec![Record::new("0-a")], Some("0-b")),
+                "0-b" => (vec![Record::new("0-b")], None),
+                "1-a" => (vec![Record::new("1-a")], Some("1-b")),
+                "2-a" => (vec![], None),
+                _ => anyhow::bail!("Unexpected iterator {iterator}"),
+            };
+
+            Ok(GetRecordsOutput {
+                records: Records::from(records),
+                next_iterator: next_iterator.map(String::from),
+            })
+        }
+
+        async fn get_shards(
+            &self,
+            _arn: &str,
+            _exclusive_shard_id: Option<String>,
+        ) -> Result<GetShardsOutput> {
+            anyhow::bail!("not used in this test")
+        }
+
+        async fn get_stream_arn(&self, _table: &str) -> Result<GetStreamArnOutput> {
+            anyhow::bail!("not used in this test")
+        }
+    }
+
+    #[tokio::test]
+    async fn it_reads_child_shards_after_their_parents_are_closed() {
+        let client: Arc<dyn Client> = Arc::new(MockClient);
+        let shards = vec![Shard::new("1", Some("0")), Shard::new::<&str>("0", None)];
+
+        let polled = Lineages::from(shards)
+            .get_records(Arc::clone(&client), "arn", "People")
+            .await;
+        assert_eq!(polled.records.len(), 1);
+        assert!(polled.records.includes("0-a"));
+        assert_eq!(polled.shards.len(), 2);
+        assert!(polled.closed.is_empty());
+        assert!(polled.read);
+
+        let polled = Lineages::from(polled.shards)
+            .get_records(Arc::clone(&client), "arn", "People")
+            .await;
+        assert_eq!(polled.records.len(), 1);
+        assert!(polled.records.includes("0-b"));
+        assert_eq!(polled.closed, vec!["0"]);
+        assert_eq!(polled.shards.len(), 1);
+        // The records written to the child after the split are read.
+        assert_eq!(
+            polled.shards[0].iterator_type(),
+            &ShardIteratorType::TrimHorizon
+        );
+
+        let polled = Lineages::from(polled.shards)
+            .get_records(Arc::clone(&client), "arn", "People")
+            .await;
+        assert_eq!(polled.records.len(), 1);
+        assert!(polled.records.includes("1-a"));
+        assert!(polled.closed.is_empty());
+    }
+
+    #[tokio::test]
+    async fn it_keeps_children_at_latest_when_parent_had_been_closed() {
+        let client: Arc<dyn Client> = Arc::new(MockClient);
+        let shards = vec![Shard::new("3", Some("2")), Shard::new::<&str>("2", None)];
+
+        let polled = Lineages::from(shards)
+            .get_records(client, "arn", "People")
+            .await;
+        assert_eq!(polled.closed, vec!["2"]);
+        assert_eq!(polled.shards.len(), 1);
+        assert_eq!(polled.shards[0].iterator_type(), &ShardIteratorType::Latest);
+    }
+
+    #[tokio::test]
+    async fn it_is_not_read_when_every_shard_fails() {
+        let client: Arc<dyn Client> = Arc::new(MockClient);
+        let shards = vec![Shard::new::<&str>("9", None)];
+
+        let polled = Lineages::from(shards)
+            .get_records(client, "arn", "People")
+            .await;
+        assert!(polled.records.is_empty());
+        assert_eq!(polled.shards.len(), 1);
+        assert!(!polled.read);
+    }
+
     //     0
     //  / \  \
     //  1  2  3
@@ -165,10 +344,10 @@ mod tests {
             let l0 = lineages.iter().find(|l| l.shard_id() == "0").unwrap();
             let l3 = lineages.iter().find(|l| l.shard_id() == "3").unwrap();
 
-            let l1 = get_child(&l0, "1");
-            let l2 = get_child(&l0, "2");
-            let l4 = get_child(&l3, "4");
-            let l5 = get_child(&l3, "5");
+            let l1 = get_child(l0, "1");
+            let l2 = get_child(l0, "2");
+            let l4 = get_child(l3, "4");
+            let l5 = get_child(l3, "5");
             assert!(l1.is_some());
             assert!(l2.is_some());
             assert!(l4.is_some());
@@ -1,8 +1,13 @@
+pub mod checkpoint;
 pub mod client;
+pub mod dedup;
 mod lineage;
 mod lineages;
 mod shard;
 pub mod stream;
 pub mod types;
 
-use super::channel::{Event, ReceiverHalf, SenderHalf, Stream};
+use super::channel::{
+    Ack, AckWatcher, Event, FanOut, OverflowPolicy, ReceiverHalf, SenderHalf, Stream,
+};
+use super::metrics::metrics;
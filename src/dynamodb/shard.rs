@@ -1,16 +1,20 @@
 use super::{
-    client::{Client, GetRecordsOutput},
-    types::Records,
+    client::{Client, GetRecordsOutput, TrimmedDataError},
+    metrics,
+    types::{Records, ShardIteratorType},
 };
 
 use anyhow::Result;
 use std::sync::Arc;
+use tracing::{error, warn};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Shard {
     id: String,
     iterator: Option<String>,
     parent: Option<String>,
+    iterator_type: ShardIteratorType,
+    sequence_number: Option<String>,
 }
 
 impl Shard {
@@ -19,6 +23,8 @@ impl Shard {
             id: id.into(),
             iterator: None,
             parent: parent.map(|s| s.into()),
+            iterator_type: ShardIteratorType::Latest,
+            sequence_number: None,
         }
     }
 
@@ -30,28 +36,192 @@ impl Shard {
         self.parent.as_deref()
     }
 
+    /// Set the position where the shard iterator starts.
+    pub fn set_position(
+        &mut self,
+        iterator_type: ShardIteratorType,
+        sequence_number: Option<String>,
+    ) {
+        self.iterator_type = iterator_type;
+        self.sequence_number = sequence_number;
+    }
+
+    /// Whether the shard starts at LATEST and hasn't been read yet.
+    pub fn is_unread_at_latest(&self) -> bool {
+        self.iterator_type == ShardIteratorType::Latest && self.iterator.is_none()
+    }
+
+    /// Read the shard from its oldest records after its parent has been closed, since they have
+    /// been written after the parent's records. A shard with a position to resume keeps it.
+    pub fn follow_parent(&mut self) {
+        if self.iterator_type == ShardIteratorType::Latest {
+            self.set_position(ShardIteratorType::TrimHorizon, None);
+            self.iterator = None;
+        }
+    }
+
+    /// Set the last delivered sequence number. The shard iterator will start right after it.
+    pub fn set_checkpoint(&mut self, sequence_number: String) {
+        self.set_position(
+            ShardIteratorType::AfterSequenceNumber,
+            Some(sequence_number),
+        );
+    }
+
+    /// Get the shard iterator at the position. If the position has been trimmed, like an old
+    /// checkpoint, the shard is read from its oldest records instead.
     pub async fn set_iterator(&mut self, client: Arc<dyn Client>, stream_arn: &str) -> Result<()> {
-        let output = client.get_iterator(stream_arn, self.id()).await?;
+        let result = client
+            .get_iterator(
+                stream_arn,
+                self.id(),
+                self.iterator_type.clone(),
+                self.sequence_number.as_deref(),
+            )
+            .await;
+
+        let output = match result {
+            Err(err)
+                if err.is::<TrimmedDataError>()
+                    && self.iterator_type != ShardIteratorType::TrimHorizon =>
+            {
+                warn!(
+                    "The position of shard \"{}\" has been trimmed. Read it from TRIM_HORIZON.",
+                    self.id
+                );
+                warn!("{:#?}", err);
+                self.set_position(ShardIteratorType::TrimHorizon, None);
+                client
+                    .get_iterator(stream_arn, self.id(), self.iterator_type.clone(), None)
+                    .await?
+            }
+            result => result?,
+        };
         self.iterator = output.iterator;
         Ok(())
     }
 
-    pub async fn get_records(self, client: Arc<dyn Client>) -> Result<(Option<Shard>, Records)> {
-        match self.iterator.as_deref() {
-            Some(iterator) => {
-                let GetRecordsOutput {
-                    records,
-                    next_iterator,
-                } = client.get_records(iterator).await?;
+    /// Read records from the shard. The returned shard is `None` if the shard has been closed.
+    /// On failures, the error is returned with the shard without its iterator so that the
+    /// iterator is acquired again, right after the last read record, on the next read.
+    pub async fn get_records(
+        mut self,
+        client: Arc<dyn Client>,
+        stream_arn: &str,
+        table: &str,
+    ) -> (Option<Shard>, Result<Records>) {
+        if self.iterator.is_none() {
+            if let Err(err) = self.set_iterator(Arc::clone(&client), stream_arn).await {
+                error!("Failed to get shard iterator: {err}");
+                error!("{:#?}", err);
+                return (Some(self), Err(err));
+            }
+        }
+
+        let iterator = match self.iterator.take() {
+            Some(iterator) => iterator,
+            None => return (None, Ok(Records::new())),
+        };
+
+        metrics().get_records.with_label_values(&[table]).inc();
+
+        match client.get_records(&iterator).await {
+            Ok(GetRecordsOutput {
+                records,
+                next_iterator,
+            }) => {
+                metrics()
+                    .records_read
+                    .with_label_values(&[table, self.id()])
+                    .inc_by(records.len() as u64);
+
+                if let Some(sequence_number) = records.last_sequence_number() {
+                    self.set_checkpoint(sequence_number.to_string());
+                }
 
                 let shard = next_iterator.map(|iterator| Shard {
                     iterator: Some(iterator),
                     ..self
                 });
 
-                Ok((shard, records))
+                (shard, Ok(records))
+            }
+            Err(err) => {
+                metrics()
+                    .get_records_errors
+                    .with_label_values(&[table])
+                    .inc();
+                error!("Failed to get records from shard: {err}");
+                error!("{:#?}", err);
+                (Some(self), Err(err))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+impl Shard {
+    pub fn iterator_type(&self) -> &ShardIteratorType {
+        &self.iterator_type
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dynamodb::client::{
+        GetIteratorOutput, GetShardsOutput, GetStreamArnOutput, TrimmedDataError,
+    };
+    use axum::async_trait;
+
+    /// Every position but TRIM_HORIZON has been trimmed.
+    struct MockClient;
+
+    #[async_trait]
+    impl Client for MockClient {
+        async fn get_iterator(
+            &self,
+            _stream_arn: &str,
+            shard_id: &str,
+            iterator_type: ShardIteratorType,
+            _sequence_number: Option<&str>,
+        ) -> Result<GetIteratorOutput> {
+            match iterator_type {
+                ShardIteratorType::TrimHorizon => Ok(GetIteratorOutput {
+                    iterator: Some(format!("{shard_id}/trim-horizon")),
+                }),
+                _ => Err(TrimmedDataError.into()),
             }
-            None => Ok((None, Records::new())),
         }
+
+        async fn get_records(&self, _iterator: &str) -> Result<GetRecordsOutput> {
+            anyhow::bail!("not used in this test")
+        }
+
+        async fn get_shards(
+            &self,
+            _arn: &str,
+            _exclusive_shard_id: Option<String>,
+        ) -> Result<GetShardsOutput> {
+            anyhow::bail!("not used in this test")
+        }
+
+        async fn get_stream_arn(&self, _table: &str) -> Result<GetStreamArnOutput> {
+            anyhow::bail!("not used in this test")
+        }
+    }
+
+    #[tokio::test]
+    async fn it_reads_shard_from_trim_horizon_when_checkpoint_is_trimmed() {
+        let mut shard = Shard::new::<&str>("0", None);
+        shard.set_checkpoint("100".into());
+
+        shard
+            .set_iterator(Arc::new(MockClient), "arn")
+            .await
+            .unwrap();
+        assert_eq!(shard.iterator.as_deref(), Some("0/trim-horizon"));
+        assert_eq!(shard.iterator_type, ShardIteratorType::TrimHorizon);
+        assert_eq!(shard.sequence_number, None);
     }
 }
@@ -4,6 +4,10 @@ use super::*;
 pub struct DynamodbStreamBuilder {
     client: Option<Arc<dyn Client>>,
     table: Option<String>,
+    position: StartingPosition,
+    checkpoint_store: Option<Arc<dyn CheckpointStore>>,
+    dedup: Option<DedupConfig>,
+    dedup_store: Option<Arc<dyn DedupStore>>,
 }
 
 impl DynamodbStreamBuilder {
@@ -11,6 +15,10 @@ impl DynamodbStreamBuilder {
         Self {
             client: None,
             table: None,
+            position: StartingPosition::default(),
+            checkpoint_store: None,
+            dedup: None,
+            dedup_store: None,
         }
     }
 
@@ -28,25 +36,57 @@ impl DynamodbStreamBuilder {
         }
     }
 
+    pub fn set_starting_position(self, position: StartingPosition) -> Self {
+        Self { position, ..self }
+    }
+
+    pub fn set_checkpoint_store(self, store: Option<Arc<dyn CheckpointStore>>) -> Self {
+        Self {
+            checkpoint_store: store,
+            ..self
+        }
+    }
+
+    pub fn set_dedup(self, dedup: Option<DedupConfig>) -> Self {
+        Self { dedup, ..self }
+    }
+
+    pub fn set_dedup_store(self, store: Option<Arc<dyn DedupStore>>) -> Self {
+        Self {
+            dedup_store: store,
+            ..self
+        }
+    }
+
     pub fn build(self) -> (DynamodbStream, DynamodbStreamHalf) {
         let client = self.client.expect("\"client\" is not set");
         let table = self.table.expect("\"table\" is not set");
 
         let (tx0, rx0) = oneshot::channel::<Event>();
-        let (tx1, rx1) = watch::channel(Records::new());
+        let fanout = FanOut::new();
+        let stats = Arc::new(Mutex::new(StreamStats::default()));
 
         let stream = DynamodbStream {
             client,
             arn: "".into(),
             table,
             rx_event: rx0,
-            tx_records: tx1,
+            tx_records: fanout.clone(),
             shards: vec![],
+            position: self.position,
+            checkpoint_store: self.checkpoint_store,
+            pending: VecDeque::new(),
+            dedup: self.dedup.map(Deduplicator::new),
+            dedup_store: self.dedup_store,
+            dedup_saved_at: Instant::now(),
+            closed: HashSet::new(),
+            stats: Arc::clone(&stats),
         };
 
         let half = DynamodbStreamHalf {
             tx_event: Some(tx0),
-            rx_records: rx1,
+            fanout,
+            stats,
         };
 
         (stream, half)
@@ -56,12 +96,31 @@ impl DynamodbStreamBuilder {
 #[derive(Debug)]
 pub struct DynamodbStreamHalf {
     tx_event: Option<oneshot::Sender<Event>>,
-    rx_records: watch::Receiver<Records>,
+    fanout: FanOut,
+    stats: Arc<Mutex<StreamStats>>,
 }
 
 impl DynamodbStreamHalf {
-    pub fn receiver(&self) -> watch::Receiver<Records> {
-        self.rx_records.clone()
+    /// Get a new records receiver for the consumer identified by `id`.
+    pub fn receiver(
+        &self,
+        id: &str,
+        capacity: usize,
+        policy: OverflowPolicy,
+    ) -> mpsc::Receiver<(Records, Ack)> {
+        self.fanout.subscribe(id, capacity, policy)
+    }
+
+    /// Stop sending records to the consumer identified by `id`.
+    pub fn remove_receiver(&self, id: &str) {
+        self.fanout.unsubscribe(id);
+    }
+
+    pub fn stats(&self) -> StreamStats {
+        self.stats
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
     }
 }
 
@@ -74,7 +133,7 @@ impl SenderHalf for DynamodbStreamHalf {
 impl Drop for DynamodbStreamHalf {
     // Close channel
     fn drop(&mut self) {
-        if let Some(tx) = self.tx_event().take() {
+        if let Some(tx) = self.tx_event() {
             let _ = tx.send(Event::Close);
         }
     }
@@ -1,28 +1,64 @@
 mod builder;
+mod stats;
 
 use super::{
+    checkpoint::{Checkpoint, CheckpointStore},
     client::{Client, GetShardsOutput},
-    lineages::Lineages,
+    dedup::{DedupConfig, DedupStore, Deduplicator},
+    lineages::{Lineages, Polled},
+    metrics,
     shard::Shard,
-    types::Records,
-    Event, ReceiverHalf, SenderHalf, Stream,
+    types::{Records, ShardIteratorType, StartingPosition},
+    Ack, AckWatcher, Event, FanOut, OverflowPolicy, ReceiverHalf, SenderHalf, Stream,
 };
 
 use anyhow::Result;
 use axum::async_trait;
-use std::sync::Arc;
-use tokio::sync::{oneshot, watch};
-use tracing::error;
+use chrono::Utc;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::{Arc, Mutex},
+};
+use tokio::{
+    sync::{mpsc, oneshot},
+    time::{sleep, Duration, Instant},
+};
+use tracing::{error, warn};
 
 pub use builder::{DynamodbStreamBuilder, DynamodbStreamHalf};
+pub use stats::StreamStats;
+
+/// How often to check whether the consumers have acknowledged the records while stopping.
+const ACK_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+/// The minimum interval between saves of the delivered event IDs.
+const DEDUP_SAVE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The progress made by records sent to the consumers. It is committed once every consumer has
+/// acknowledged the records.
+#[derive(Debug)]
+struct Pending {
+    watcher: AckWatcher,
+    checkpoints: Vec<Checkpoint>,
+    event_ids: Vec<String>,
+}
 
 pub struct DynamodbStream {
     client: Arc<dyn Client>,
     arn: String,
     table: String,
     rx_event: oneshot::Receiver<Event>,
-    tx_records: watch::Sender<Records>,
+    tx_records: FanOut,
     shards: Vec<Shard>,
+    position: StartingPosition,
+    checkpoint_store: Option<Arc<dyn CheckpointStore>>,
+    /// The progress of records sent, in the order they were read.
+    pending: VecDeque<Pending>,
+    dedup: Option<Deduplicator>,
+    dedup_store: Option<Arc<dyn DedupStore>>,
+    dedup_saved_at: Instant,
+    /// The IDs of closed shards which the stream still describes.
+    closed: HashSet<String>,
+    stats: Arc<Mutex<StreamStats>>,
 }
 
 impl DynamodbStream {
@@ -33,6 +69,184 @@ impl DynamodbStream {
     fn client(&self) -> Arc<dyn Client> {
         Arc::clone(&self.client)
     }
+
+    /// Set the starting position to each shard.
+    fn set_positions(&self, shards: Vec<Shard>) -> Vec<Shard> {
+        let all = shards.clone();
+
+        shards
+            .into_iter()
+            .map(|mut shard| {
+                match &self.position {
+                    StartingPosition::Latest => {
+                        shard.set_position(ShardIteratorType::Latest, None);
+                    }
+                    StartingPosition::TrimHorizon | StartingPosition::AtTimestamp(_) => {
+                        shard.set_position(ShardIteratorType::TrimHorizon, None);
+                    }
+                    StartingPosition::AtSequenceNumber {
+                        shard_id,
+                        sequence_number,
+                    } => {
+                        if shard.id() == shard_id {
+                            shard.set_position(
+                                ShardIteratorType::AtSequenceNumber,
+                                Some(sequence_number.clone()),
+                            );
+                        } else if descends_from(&shard, shard_id, &all) {
+                            shard.set_position(ShardIteratorType::TrimHorizon, None);
+                        } else {
+                            shard.set_position(ShardIteratorType::Latest, None);
+                        }
+                    }
+                }
+                shard
+            })
+            .collect()
+    }
+
+    /// Set the position to the shards found after the stream started. They are children of the
+    /// shards the stream has read so that they are read from their oldest records.
+    fn set_new_positions(&self, shards: Vec<Shard>) -> Vec<Shard> {
+        shards
+            .into_iter()
+            .map(|mut shard| {
+                shard.set_position(ShardIteratorType::TrimHorizon, None);
+                shard
+            })
+            .collect()
+    }
+
+    /// Set the statistics of the poll. The poll time and the lag are kept as they were when
+    /// no shard has been read.
+    fn set_stats(&self, records: &Records, read: bool) {
+        let mut stats = self
+            .stats
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        stats.open_shards = self.shards.len();
+        if !read {
+            return;
+        }
+
+        let now = Utc::now();
+        let lag_ms = records
+            .latest_created_at()
+            .map(|created_at| (now - *created_at).num_milliseconds().max(0))
+            .unwrap_or(0);
+        stats.last_polled_at = Some(now);
+        stats.lag_ms = Some(lag_ms);
+
+        metrics()
+            .iterator_age
+            .with_label_values(&[&self.table])
+            .set(lag_ms as f64 / 1000.0);
+    }
+
+    /// Load the last delivered sequence number of each shard.
+    async fn load_checkpoints(&self, shards: &[Shard]) -> Result<HashMap<String, String>> {
+        let mut checkpoints: HashMap<String, String> = HashMap::new();
+
+        if let Some(store) = self.checkpoint_store.as_ref() {
+            for shard in shards {
+                if let Some(sequence_number) = store.load(&self.table, shard.id()).await? {
+                    checkpoints.insert(shard.id().to_string(), sequence_number);
+                }
+            }
+        }
+
+        Ok(checkpoints)
+    }
+
+    /// Remember the events delivered before the restart of the process.
+    async fn restore_events(&mut self) -> Result<()> {
+        if let (Some(dedup), Some(store)) = (self.dedup.as_mut(), self.dedup_store.as_ref()) {
+            dedup.restore(store.load(&self.table).await?);
+        }
+        Ok(())
+    }
+
+    /// Drop the records which have been delivered.
+    fn dedup(&mut self, records: &mut Records) {
+        if let Some(dedup) = self.dedup.as_mut() {
+            let suppressed = dedup.dedup(records);
+            if suppressed > 0 {
+                metrics()
+                    .duplicates_suppressed
+                    .with_label_values(&[&self.table])
+                    .inc_by(suppressed as u64);
+            }
+        }
+    }
+
+    /// Save the progress of the records acknowledged by every consumer. It stops at the first
+    /// unacknowledged records so that no checkpoint skips records still being delivered.
+    async fn save_acked(&mut self) -> Result<()> {
+        let mut checkpoints: Vec<Checkpoint> = vec![];
+
+        while self
+            .pending
+            .front()
+            .is_some_and(|pending| pending.watcher.is_acked())
+        {
+            if let Some(mut pending) = self.pending.pop_front() {
+                checkpoints.append(&mut pending.checkpoints);
+                if let Some(dedup) = self.dedup.as_mut() {
+                    dedup.acknowledge(pending.event_ids);
+                }
+            }
+        }
+
+        if let Some(store) = self.checkpoint_store.as_ref() {
+            store.save(&self.table, checkpoints).await?;
+        }
+        Ok(())
+    }
+
+    fn is_lost(&self) -> bool {
+        self.pending
+            .front()
+            .is_some_and(|pending| pending.watcher.is_lost())
+    }
+
+    /// Read the shards again from the last checkpoints after a consumer has lost records. The
+    /// shards are described again on the next iteration. Without a checkpoint store, the lost
+    /// records can't be read again and are given up. Either way, the event IDs of the rewound
+    /// records are released so that the records are delivered when they are read again.
+    fn rewind(&mut self) {
+        let rewound: Vec<Pending> = if self.checkpoint_store.is_some() {
+            warn!(
+                "Records of \"{}\" table have been lost before delivered. Read them again from the last checkpoints.",
+                self.table
+            );
+            self.arn.clear();
+            self.shards.clear();
+            self.closed.clear();
+            self.pending.drain(..).collect()
+        } else {
+            warn!(
+                "Records of \"{}\" table have been lost before delivered.",
+                self.table
+            );
+            self.pending.pop_front().into_iter().collect()
+        };
+
+        if let Some(dedup) = self.dedup.as_mut() {
+            for pending in rewound {
+                dedup.release(&pending.event_ids);
+            }
+        }
+    }
+
+    async fn save_events(&mut self) -> Result<()> {
+        if let (Some(dedup), Some(store)) = (self.dedup.as_mut(), self.dedup_store.as_ref()) {
+            if let Some(events) = dedup.take_changes() {
+                store.save(&self.table, events).await?;
+            }
+        }
+        self.dedup_saved_at = Instant::now();
+        Ok(())
+    }
 }
 
 impl ReceiverHalf for DynamodbStream {
@@ -47,14 +261,26 @@ impl Stream for DynamodbStream {
         self.table.as_str()
     }
 
-    fn tx_records(&self) -> &watch::Sender<Records> {
+    fn tx_records(&self) -> &FanOut {
         &self.tx_records
     }
 
     async fn init(&mut self) -> Result<()> {
+        // Keep the shards when the stream is restarted after it has been initialized so that it
+        // resumes where it failed.
+        if !self.arn.is_empty() {
+            return Ok(());
+        }
+
         let arn = self.client.get_stream_arn(&self.table).await?.stream_arn;
-        let shards = get_all_shards(Arc::clone(&self.client), &arn).await?;
-        let shards = set_shard_iterators(Arc::clone(&self.client), &arn, shards).await;
+        let shards = get_all_shards(Arc::clone(&self.client), &arn, &self.table).await?;
+        // Checkpoints take precedence over the starting position.
+        let checkpoints = self.load_checkpoints(&shards).await?;
+        let shards = match checkpoints.is_empty() {
+            true => self.set_positions(shards),
+            false => resume_positions(shards, &checkpoints),
+        };
+        self.restore_events().await?;
 
         self.arn = arn;
         self.shards = shards;
@@ -62,42 +288,113 @@ impl Stream for DynamodbStream {
         Ok(())
     }
 
-    async fn iterate(&mut self) -> Result<Records> {
+    async fn iterate(&mut self) -> Result<(Records, Ack)> {
+        // Describe the shards again after they have been rewound.
+        self.init().await?;
+
         // Get records from current shards.
         let mut shards: Vec<Shard> = vec![];
         shards.append(&mut self.shards);
 
-        let lineages = Lineages::from(shards);
-        let (mut records, mut shards) = lineages.get_records(self.client()).await;
+        let Polled {
+            mut records,
+            shards,
+            closed,
+            checkpoints,
+            read,
+        } = Lineages::from(shards)
+            .get_records(self.client(), &self.arn, &self.table)
+            .await;
+        self.closed.extend(closed);
+        self.shards = shards;
 
-        // Refresh shards
-        // 1. Get shards which the stream doesn't have.
-        let new_shards = get_all_shards(self.client(), &self.arn)
-            .await?
-            .into_iter()
-            .filter(|shard| !shards.iter().any(|s| s.id() == shard.id()))
-            .collect::<Vec<Shard>>();
+        // Refresh shards. Records already read are delivered even if this fails.
+        match get_all_shards(self.client(), &self.arn, &self.table).await {
+            Ok(all_shards) => {
+                // 1. Forget closed shards which have been trimmed from the stream.
+                self.closed
+                    .retain(|id| all_shards.iter().any(|shard| shard.id() == id));
 
-        // 2. Set iterators to new shards.
-        let mut new_shards = set_shard_iterators(self.client(), &self.arn, new_shards).await;
+                // 2. Get shards which the stream has never read.
+                let new_shards = all_shards
+                    .into_iter()
+                    .filter(|shard| {
+                        !self.closed.contains(shard.id())
+                            && !self.shards.iter().any(|s| s.id() == shard.id())
+                    })
+                    .collect::<Vec<Shard>>();
 
-        // 3. Append new shards
-        shards.append(&mut new_shards);
-        self.shards = shards;
+                // 3. Append new shards. Their iterators are acquired when they are read.
+                let mut new_shards = self.set_new_positions(new_shards);
+                self.shards.append(&mut new_shards);
+            }
+            Err(err) => {
+                error!("Failed to refresh shards: {err}");
+                error!("{:#?}", err);
+            }
+        }
+
+        self.set_stats(&records, read);
+
+        records.retain(|record| self.position.includes(record));
+        self.dedup(&mut records);
 
-        records.sort();
-        Ok(records)
+        let event_ids = match self.dedup {
+            Some(_) => records
+                .iter()
+                .filter_map(|record| record.event_id().map(String::from))
+                .collect(),
+            None => vec![],
+        };
+        let (ack, watcher) = Ack::new();
+        self.pending.push_back(Pending {
+            watcher,
+            checkpoints,
+            event_ids,
+        });
+
+        Ok((records, ack))
+    }
+
+    async fn commit(&mut self) -> Result<()> {
+        self.save_acked().await?;
+        if self.is_lost() {
+            self.rewind();
+        }
+
+        if self.dedup_saved_at.elapsed() >= DEDUP_SAVE_INTERVAL {
+            self.save_events().await?;
+        }
+        Ok(())
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        // Wait until the consumers deliver the records they hold, or give up if some are lost.
+        // The stream is aborted if it takes too long.
+        loop {
+            self.save_acked().await?;
+            if self.pending.is_empty() || self.is_lost() {
+                break;
+            }
+            sleep(ACK_CHECK_INTERVAL).await;
+        }
+
+        self.save_events().await
     }
 }
 
-async fn get_all_shards(client: Arc<dyn Client>, stream_arn: &str) -> Result<Vec<Shard>> {
+async fn get_all_shards(
+    client: Arc<dyn Client>,
+    stream_arn: &str,
+    table: &str,
+) -> Result<Vec<Shard>> {
     let GetShardsOutput {
         mut shards,
         mut last_shard_id,
-    } = client.get_shards(stream_arn, None).await?;
+    } = describe_stream(&client, stream_arn, table, None).await?;
 
     while last_shard_id.is_some() {
-        let mut output = client.get_shards(stream_arn, last_shard_id.take()).await?;
+        let mut output = describe_stream(&client, stream_arn, table, last_shard_id.take()).await?;
         shards.append(&mut output.shards);
         last_shard_id = output.last_shard_id;
     }
@@ -105,49 +402,268 @@ async fn get_all_shards(client: Arc<dyn Client>, stream_arn: &str) -> Result<Vec
     Ok(shards)
 }
 
-async fn set_shard_iterators(
-    client: Arc<dyn Client>,
+async fn describe_stream(
+    client: &Arc<dyn Client>,
     stream_arn: &str,
-    shards: Vec<Shard>,
-) -> Vec<Shard> {
-    let mut output: Vec<Shard> = vec![];
-    let buf_size = channel_size(&shards);
-    let (tx, mut rx) = tokio::sync::mpsc::channel::<Shard>(buf_size);
-
-    for mut shard in shards {
-        let client = Arc::clone(&client);
-        let tx = tx.clone();
-        let arn = stream_arn.to_string();
-
-        tokio::spawn(async move {
-            if let Err(err) = shard.set_iterator(client, &arn).await {
-                error!("Failed to get shard iterator: {err}");
-                error!("{:#?}", err);
-                return;
-            }
+    table: &str,
+    exclusive_shard_id: Option<String>,
+) -> Result<GetShardsOutput> {
+    metrics().describe_stream.with_label_values(&[table]).inc();
 
-            // Send shard only if the set_iterator method succeeds
-            if let Err(err) = tx.send(shard).await {
-                error!("Failed to send shard: {err}");
-                error!("{:#?}", err);
+    client
+        .get_shards(stream_arn, exclusive_shard_id)
+        .await
+        .inspect_err(|_| {
+            metrics()
+                .describe_stream_errors
+                .with_label_values(&[table])
+                .inc();
+        })
+}
+
+/// Set the position to each shard of a stream which has been read before, so that no record
+/// written while the process was down is skipped. Each shard resumes right after its checkpoint.
+/// A shard without a checkpoint is read from its oldest records because it has been created
+/// since then, like a child of a checkpointed shard, or had no records to deliver. Only the
+/// ancestors of checkpointed shards are skipped as they have been read to their end.
+fn resume_positions(shards: Vec<Shard>, checkpoints: &HashMap<String, String>) -> Vec<Shard> {
+    let all = shards.clone();
+
+    shards
+        .into_iter()
+        .map(|mut shard| {
+            let is_ancestor = all
+                .iter()
+                .any(|s| checkpoints.contains_key(s.id()) && descends_from(s, shard.id(), &all));
+
+            match checkpoints.get(shard.id()) {
+                Some(sequence_number) => shard.set_checkpoint(sequence_number.clone()),
+                None if is_ancestor => shard.set_position(ShardIteratorType::Latest, None),
+                None => shard.set_position(ShardIteratorType::TrimHorizon, None),
             }
-        });
-    }
+            shard
+        })
+        .collect()
+}
+
+/// Check if the shard is derived from the ancestor shard.
+fn descends_from(shard: &Shard, ancestor_id: &str, shards: &[Shard]) -> bool {
+    let mut parent = shard.parent();
 
-    drop(tx);
+    while let Some(parent_id) = parent {
+        if parent_id == ancestor_id {
+            return true;
+        }
 
-    while let Some(shard) = rx.recv().await {
-        // Push shard having its iterator
-        output.push(shard);
+        parent = shards
+            .iter()
+            .find(|s| s.id() == parent_id)
+            .and_then(|s| s.parent());
     }
 
-    output
+    false
 }
 
-fn channel_size(shards: &[Shard]) -> usize {
-    if shards.is_empty() {
-        1
-    } else {
-        shards.len()
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dynamodb::{
+        checkpoint::FileCheckpointStore,
+        client::{GetIteratorOutput, GetRecordsOutput, GetStreamArnOutput},
+        types::Record,
+    };
+    use ulid::Ulid;
+
+    /// Each shard has a record on every read. The iterator is the sequence number of the next
+    /// record, starting with 1.
+    struct MockClient {
+        shards: Vec<Shard>,
+    }
+
+    #[async_trait]
+    impl Client for MockClient {
+        async fn get_iterator(
+            &self,
+            _stream_arn: &str,
+            shard_id: &str,
+            _iterator_type: ShardIteratorType,
+            _sequence_number: Option<&str>,
+        ) -> Result<GetIteratorOutput> {
+            Ok(GetIteratorOutput {
+                iterator: Some(format!("{shard_id}/1")),
+            })
+        }
+
+        async fn get_records(&self, iterator: &str) -> Result<GetRecordsOutput> {
+            let (shard_id, sequence_number) = iterator.split_once('/').unwrap();
+            let sequence_number: u64 = sequence_number.parse()?;
+            let record = Record::new(format!("{shard_id}-{sequence_number}"))
+                .set_sequence_number(sequence_number.to_string());
+
+            Ok(GetRecordsOutput {
+                records: Records::from(vec![record]),
+                next_iterator: Some(format!("{shard_id}/{}", sequence_number + 1)),
+            })
+        }
+
+        async fn get_shards(
+            &self,
+            _arn: &str,
+            _exclusive_shard_id: Option<String>,
+        ) -> Result<GetShardsOutput> {
+            Ok(GetShardsOutput {
+                shards: self.shards.clone(),
+                last_shard_id: None,
+            })
+        }
+
+        async fn get_stream_arn(&self, _table: &str) -> Result<GetStreamArnOutput> {
+            Ok(GetStreamArnOutput {
+                stream_arn: "arn".into(),
+            })
+        }
+    }
+
+    async fn checkpoint_store() -> (Arc<dyn CheckpointStore>, std::path::PathBuf) {
+        let path = std::env::temp_dir().join(format!("{}.json", Ulid::new()));
+        let store = FileCheckpointStore::open(&path).await.unwrap();
+        (Arc::new(store), path)
+    }
+
+    //     0
+    //    / \
+    //   1*  2   4
+    //  /
+    // 3
+    #[test]
+    fn it_resumes_shards_without_checkpoints_by_their_lineage() {
+        let shards = vec![
+            Shard::new::<&str>("0", None),
+            Shard::new("1", Some("0")),
+            Shard::new("2", Some("0")),
+            Shard::new("3", Some("1")),
+            Shard::new::<&str>("4", None),
+        ];
+        let checkpoints = HashMap::from([("1".to_string(), "100".to_string())]);
+
+        let position = |id: &str, parent: Option<&str>, iterator_type, sequence_number| {
+            let mut shard = Shard::new(id, parent);
+            shard.set_position(iterator_type, sequence_number);
+            shard
+        };
+
+        assert_eq!(
+            resume_positions(shards, &checkpoints),
+            vec![
+                // The parent of the checkpointed shard has been read to its end.
+                position("0", None, ShardIteratorType::Latest, None),
+                position(
+                    "1",
+                    Some("0"),
+                    ShardIteratorType::AfterSequenceNumber,
+                    Some("100".into())
+                ),
+                // Shards without a checkpoint, including the child created while the process
+                // was down, are read from their oldest records.
+                position("2", Some("0"), ShardIteratorType::TrimHorizon, None),
+                position("3", Some("1"), ShardIteratorType::TrimHorizon, None),
+                position("4", None, ShardIteratorType::TrimHorizon, None),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn it_commits_checkpoints_after_records_are_acknowledged() {
+        let (store, path) = checkpoint_store().await;
+        let (mut stream, half) = DynamodbStream::builder()
+            .set_client(Arc::new(MockClient {
+                shards: vec![Shard::new::<&str>("0", None)],
+            }))
+            .set_table("People")
+            .set_checkpoint_store(Some(Arc::clone(&store)))
+            .build();
+        let mut rx = half.receiver("0", 2, OverflowPolicy::Block);
+        stream.init().await.unwrap();
+
+        for _ in 0..2 {
+            let (records, ack) = stream.iterate().await.unwrap();
+            stream.tx_records().send(records, ack).await;
+            stream.commit().await.unwrap();
+        }
+        let (_, first) = rx.recv().await.unwrap();
+        let (_, second) = rx.recv().await.unwrap();
+
+        // Nothing is checkpointed while the first records are not delivered.
+        assert_eq!(store.load("People", "0").await.unwrap(), None);
+        second.done();
+        stream.commit().await.unwrap();
+        assert_eq!(store.load("People", "0").await.unwrap(), None);
+
+        first.done();
+        stream.commit().await.unwrap();
+        assert_eq!(
+            store.load("People", "0").await.unwrap(),
+            Some("2".to_string())
+        );
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn it_delivers_rewound_records_again() {
+        for with_store in [true, false] {
+            let (store, path) = checkpoint_store().await;
+            let (mut stream, half) = DynamodbStream::builder()
+                .set_client(Arc::new(MockClient {
+                    shards: vec![Shard::new::<&str>("0", None)],
+                }))
+                .set_table("People")
+                .set_checkpoint_store(with_store.then(|| Arc::clone(&store)))
+                .set_dedup(Some(DedupConfig::new(Duration::from_secs(60))))
+                .build();
+            let mut rx = half.receiver("0", 1, OverflowPolicy::Block);
+            stream.init().await.unwrap();
+
+            let (records, ack) = stream.iterate().await.unwrap();
+            assert!(records.includes("0-1"));
+            stream.tx_records().send(records, ack).await;
+            // The consumer is aborted with the records.
+            drop(rx.recv().await.unwrap());
+            stream.commit().await.unwrap();
+            assert!(stream.pending.is_empty());
+
+            // The shard is read again from its checkpoint, or the same event comes again.
+            let again = match with_store {
+                true => stream.iterate().await.unwrap().0,
+                false => {
+                    let mut records = Records::from(vec![Record::new("0-1")]);
+                    stream.dedup(&mut records);
+                    records
+                }
+            };
+            assert!(again.includes("0-1"));
+
+            let _ = std::fs::remove_file(&path);
+        }
+    }
+
+    //     0
+    //    / \
+    //   1   2
+    //  /
+    // 3
+    #[test]
+    fn it_checks_if_the_shard_descends_from_the_ancestor() {
+        let shards = vec![
+            Shard::new::<&str>("0", None),
+            Shard::new("1", Some("0")),
+            Shard::new("2", Some("0")),
+            Shard::new("3", Some("1")),
+        ];
+
+        assert!(descends_from(&shards[3], "1", &shards));
+        assert!(descends_from(&shards[3], "0", &shards));
+        assert!(!descends_from(&shards[3], "2", &shards));
+        assert!(!descends_from(&shards[0], "0", &shards));
     }
 }
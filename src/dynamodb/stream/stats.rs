@@ -0,0 +1,13 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// The runtime statistics of a stream.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct StreamStats {
+    /// When the stream read records from any shard for the last time.
+    pub last_polled_at: Option<DateTime<Utc>>,
+    pub open_shards: usize,
+    /// How far the newest record read in the last successful poll was behind. `None` until a
+    /// poll succeeds, and zero when the poll read no records.
+    pub lag_ms: Option<i64>,
+}
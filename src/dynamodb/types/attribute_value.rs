@@ -1,10 +1,14 @@
-use super::into_str;
+use super::into_base64;
 
 use aws_sdk_dynamodbstreams::types;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
 use std::collections::HashMap;
 
-#[derive(Debug, Serialize, Clone, Eq, PartialEq)]
+/// The most significant digits which an `f64` keeps without losing precision.
+const F64_DIGITS: usize = 15;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum AttributeValue {
     B(String),
@@ -23,10 +27,10 @@ pub enum AttributeValue {
 impl From<types::AttributeValue> for AttributeValue {
     fn from(value: types::AttributeValue) -> AttributeValue {
         match value {
-            types::AttributeValue::B(v) => AttributeValue::B(into_str(v)),
+            types::AttributeValue::B(v) => AttributeValue::B(into_base64(v)),
             types::AttributeValue::Bool(v) => AttributeValue::Bool(v),
             types::AttributeValue::Bs(v) => {
-                AttributeValue::Bs(v.into_iter().map(into_str).collect())
+                AttributeValue::Bs(v.into_iter().map(into_base64).collect())
             }
             types::AttributeValue::L(v) => {
                 AttributeValue::L(v.into_iter().map(AttributeValue::from).collect())
@@ -48,6 +52,60 @@ impl From<types::AttributeValue> for AttributeValue {
     }
 }
 
+impl AttributeValue {
+    /// Turn the value into native JSON without the type descriptors.
+    pub fn unmarshall(&self) -> Value {
+        match self {
+            Self::B(v) => Value::String(v.clone()),
+            Self::Bool(v) => Value::Bool(*v),
+            Self::Bs(v) => Value::Array(v.iter().cloned().map(Value::String).collect()),
+            Self::L(v) => Value::Array(v.iter().map(AttributeValue::unmarshall).collect()),
+            Self::M(v) => Value::Object(unmarshall_item(v)),
+            Self::N(v) => to_number(v),
+            Self::Ns(v) => Value::Array(v.iter().map(|n| to_number(n)).collect()),
+            Self::Null(_) | Self::Unknown => Value::Null,
+            Self::S(v) => Value::String(v.clone()),
+            Self::Ss(v) => Value::Array(v.iter().cloned().map(Value::String).collect()),
+        }
+    }
+}
+
+/// Turn an item into a native JSON object.
+pub fn unmarshall_item(item: &HashMap<String, AttributeValue>) -> Map<String, Value> {
+    item.iter()
+        .map(|(key, value)| (key.clone(), value.unmarshall()))
+        .collect()
+}
+
+/// DynamoDB numbers have up to 38 digits. Keep them as strings if a JSON number can't hold
+/// them exactly.
+fn to_number(n: &str) -> Value {
+    if let Ok(i) = n.parse::<i64>() {
+        return Value::from(i);
+    }
+    if let Ok(u) = n.parse::<u64>() {
+        return Value::from(u);
+    }
+    if significant_digits(n) <= F64_DIGITS {
+        if let Some(f) = n.parse::<f64>().ok().and_then(Number::from_f64) {
+            return Value::Number(f);
+        }
+    }
+    Value::String(n.to_string())
+}
+
+fn significant_digits(n: &str) -> usize {
+    let mantissa = n.split(['e', 'E']).next().unwrap_or_default();
+    let digits: String = mantissa.chars().filter(char::is_ascii_digit).collect();
+    let digits = digits.trim_start_matches('0');
+    let digits = if mantissa.contains('.') {
+        digits.trim_end_matches('0')
+    } else {
+        digits
+    };
+    digits.len()
+}
+
 #[cfg(test)]
 mod tests {
     use super::super::{into_chrono, into_item};
@@ -56,9 +114,12 @@ mod tests {
     use chrono::{DateTime, Utc};
 
     #[test]
-    fn it_transforms_blob_into_string() {
+    fn it_transforms_blob_into_base64() {
         let b = blob("Hello");
-        assert_eq!(into_str(b), "Hello".to_string());
+        assert_eq!(into_base64(b), "SGVsbG8=".to_string());
+
+        let b = primitives::Blob::new(vec![0xff, 0x00, 0xfe]);
+        assert_eq!(into_base64(b), "/wD+".to_string());
     }
 
     #[test]
@@ -193,6 +254,61 @@ mod tests {
         assert_eq!(json, expected);
     }
 
+    #[test]
+    fn it_unmarshalls_attribute_values_into_native_json() {
+        let mut map: HashMap<String, AttributeValue> = HashMap::new();
+        map.insert("Name".into(), AttributeValue::S("Joe".into()));
+        map.insert(
+            "Tags".into(),
+            AttributeValue::Ss(vec!["a".into(), "b".into()]),
+        );
+        map.insert(
+            "Scores".into(),
+            AttributeValue::Ns(vec!["1".into(), "2.5".into()]),
+        );
+        map.insert("Avatar".into(), AttributeValue::B("SGVsbG8=".into()));
+        map.insert("Deleted".into(), AttributeValue::Null(true));
+        map.insert(
+            "Items".into(),
+            AttributeValue::L(vec![
+                AttributeValue::Bool(true),
+                AttributeValue::N("-3".into()),
+            ]),
+        );
+
+        let json = AttributeValue::M(map).unmarshall();
+        let expected = serde_json::json!({
+            "Name": "Joe",
+            "Tags": ["a", "b"],
+            "Scores": [1, 2.5],
+            "Avatar": "SGVsbG8=",
+            "Deleted": null,
+            "Items": [true, -3]
+        });
+        assert_eq!(json, expected);
+    }
+
+    #[test]
+    fn it_unmarshalls_numbers_without_losing_precision() {
+        let n = |v: &str| AttributeValue::N(v.into()).unmarshall();
+
+        assert_eq!(n("42"), serde_json::json!(42));
+        assert_eq!(
+            n("18446744073709551615"),
+            serde_json::json!(18446744073709551615u64)
+        );
+        assert_eq!(n("123.450"), serde_json::json!(123.45));
+        assert_eq!(n("1E+3"), serde_json::json!(1000.0));
+        assert_eq!(
+            n("12345678901234567890123"),
+            serde_json::json!("12345678901234567890123")
+        );
+        assert_eq!(
+            n("0.12345678901234567"),
+            serde_json::json!("0.12345678901234567")
+        );
+    }
+
     fn blob(val: &str) -> primitives::Blob {
         primitives::Blob::new(val.as_bytes().to_vec())
     }
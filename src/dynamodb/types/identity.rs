@@ -1,13 +1,33 @@
 use aws_sdk_dynamodbstreams::types;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Clone, Eq, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Identity {
     principal_id: Option<String>,
     r#type: Option<String>,
 }
 
+/// The principal of the deletions by TTL.
+const TTL_PRINCIPAL_ID: &str = "dynamodb.amazonaws.com";
+const TTL_TYPE: &str = "Service";
+
+impl Identity {
+    pub fn principal_id(&self) -> Option<&str> {
+        self.principal_id.as_deref()
+    }
+
+    pub fn r#type(&self) -> Option<&str> {
+        self.r#type.as_deref()
+    }
+
+    /// Check if the record was made by DynamoDB deleting an expired item.
+    pub fn is_ttl(&self) -> bool {
+        self.principal_id.as_deref() == Some(TTL_PRINCIPAL_ID)
+            && self.r#type.as_deref() == Some(TTL_TYPE)
+    }
+}
+
 impl From<types::Identity> for Identity {
     fn from(value: types::Identity) -> Identity {
         Identity {
@@ -1,29 +1,35 @@
 mod attribute_value;
 mod identity;
 mod operation_type;
+mod patch_operation;
 mod record;
 mod records;
+mod removal_reason;
 mod shard_iterator_type;
+mod starting_position;
 mod stream_record;
-mod stream_status;
 mod stream_view_type;
 
 pub use attribute_value::AttributeValue;
 pub use identity::Identity;
 pub use operation_type::OperationType;
+pub use patch_operation::PatchOperation;
 pub use record::Record;
 pub use records::Records;
+pub use removal_reason::RemovalReason;
 pub use shard_iterator_type::ShardIteratorType;
+pub use starting_position::StartingPosition;
 pub use stream_record::StreamRecord;
-pub use stream_status::StreamStatus;
 pub use stream_view_type::StreamViewType;
 
 use aws_sdk_dynamodbstreams::{primitives, types};
+use base64::{engine::general_purpose::STANDARD, Engine};
 use chrono::{DateTime, Utc};
 use std::collections::HashMap;
 
-fn into_str(blob: primitives::Blob) -> String {
-    String::from_utf8_lossy(&blob.into_inner()).into_owned()
+/// Binaries are base64-encoded as in the payload of Lambda.
+fn into_base64(blob: primitives::Blob) -> String {
+    STANDARD.encode(blob.into_inner())
 }
 
 fn into_chrono(datetime: primitives::DateTime) -> DateTime<Utc> {
@@ -1,7 +1,7 @@
 use aws_sdk_dynamodbstreams::types;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Clone, Eq, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum OperationType {
     Insert,
@@ -10,6 +10,17 @@ pub enum OperationType {
     Unknown,
 }
 
+impl OperationType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Insert => "INSERT",
+            Self::Modify => "MODIFY",
+            Self::Remove => "REMOVE",
+            Self::Unknown => "UNKNOWN",
+        }
+    }
+}
+
 impl From<types::OperationType> for OperationType {
     fn from(value: types::OperationType) -> OperationType {
         match value {
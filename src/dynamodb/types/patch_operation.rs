@@ -0,0 +1,201 @@
+use super::AttributeValue;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+
+/// An operation of RFC 6902 JSON Patch turning the old image into the new one. The path
+/// goes through the type descriptors of maps, like `/Profile/M/Age`, so that it applies to
+/// the image in the DynamoDB JSON.
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum PatchOperation {
+    Add { path: String, value: AttributeValue },
+    Remove { path: String },
+    Replace { path: String, value: AttributeValue },
+}
+
+impl PatchOperation {
+    /// Compute the operations between the images. Maps are compared attribute by attribute,
+    /// and the other values are replaced as a whole.
+    pub fn diff(
+        old: &HashMap<String, AttributeValue>,
+        new: &HashMap<String, AttributeValue>,
+    ) -> Vec<PatchOperation> {
+        let mut operations = vec![];
+        diff_items(old, new, "", &mut operations);
+        operations
+    }
+
+    /// Serialize the operation with the path and the value in native JSON, like
+    /// `/Profile/Age`, so that it applies to the image in native JSON.
+    pub fn to_plain_json(&self) -> serde_json::Result<Value> {
+        let mut value = serde_json::to_value(self)?;
+        match self {
+            Self::Add { path, value: v } | Self::Replace { path, value: v } => {
+                value["path"] = plain_path(path).into();
+                value["value"] = v.unmarshall();
+            }
+            Self::Remove { path } => {
+                value["path"] = plain_path(path).into();
+            }
+        }
+        Ok(value)
+    }
+}
+
+/// Remove the type descriptors of maps, which are every other token after the attribute name.
+fn plain_path(path: &str) -> String {
+    path.split('/')
+        .skip(1)
+        .step_by(2)
+        .fold(String::new(), |path, token| format!("{path}/{token}"))
+}
+
+fn diff_items(
+    old: &HashMap<String, AttributeValue>,
+    new: &HashMap<String, AttributeValue>,
+    prefix: &str,
+    operations: &mut Vec<PatchOperation>,
+) {
+    let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+
+    for key in keys {
+        let path = format!("{prefix}/{}", escape(key));
+        match (old.get(key), new.get(key)) {
+            (Some(AttributeValue::M(o)), Some(AttributeValue::M(n))) => {
+                diff_items(o, n, &format!("{path}/M"), operations)
+            }
+            (Some(o), Some(n)) if o != n => operations.push(PatchOperation::Replace {
+                path,
+                value: n.clone(),
+            }),
+            (Some(_), None) => operations.push(PatchOperation::Remove { path }),
+            (None, Some(n)) => operations.push(PatchOperation::Add {
+                path,
+                value: n.clone(),
+            }),
+            _ => {}
+        }
+    }
+}
+
+/// Escape a key as a reference token of JSON Pointer.
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dynamodb::types::attribute_value::unmarshall_item;
+    use serde_json::json;
+
+    fn item(value: Value) -> HashMap<String, AttributeValue> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn it_computes_json_patch_between_images() {
+        let old = item(json!({
+            "Id": { "N": "1" },
+            "Name": { "S": "Joe" },
+            "Nickname": { "S": "JJ" },
+            "Profile": { "M": { "Age": { "N": "35" }, "City": { "S": "Tokyo" } } },
+            "Tags": { "SS": ["a"] }
+        }));
+        let new = item(json!({
+            "Id": { "N": "1" },
+            "Name": { "S": "Joseph" },
+            "Profile": { "M": { "Age": { "N": "36" }, "City": { "S": "Tokyo" } } },
+            "Tags": { "SS": ["a", "b"] },
+            "a/b~c": { "BOOL": true }
+        }));
+
+        let operations = PatchOperation::diff(&old, &new);
+        let json = serde_json::to_value(&operations).unwrap();
+        assert_eq!(
+            json,
+            json!([
+                { "op": "replace", "path": "/Name", "value": { "S": "Joseph" } },
+                { "op": "remove", "path": "/Nickname" },
+                { "op": "replace", "path": "/Profile/M/Age", "value": { "N": "36" } },
+                { "op": "replace", "path": "/Tags", "value": { "SS": ["a", "b"] } },
+                { "op": "add", "path": "/a~1b~0c", "value": { "BOOL": true } }
+            ])
+        );
+
+        let plain: Vec<Value> = operations
+            .iter()
+            .map(|op| op.to_plain_json().unwrap())
+            .collect();
+        assert_eq!(
+            plain[0],
+            json!({ "op": "replace", "path": "/Name", "value": "Joseph" })
+        );
+        assert_eq!(plain[1], json!({ "op": "remove", "path": "/Nickname" }));
+        assert_eq!(
+            plain[2],
+            json!({ "op": "replace", "path": "/Profile/Age", "value": 36 })
+        );
+    }
+
+    /// Apply the operations of JSON Patch which `diff` makes.
+    fn apply(mut doc: Value, operations: &[Value]) -> Value {
+        for operation in operations {
+            let path = operation["path"].as_str().unwrap();
+            let (parent, token) = path.rsplit_once('/').unwrap();
+            let key = token.replace("~1", "/").replace("~0", "~");
+            let object = doc.pointer_mut(parent).unwrap().as_object_mut().unwrap();
+            match operation["op"].as_str().unwrap() {
+                "add" | "replace" => {
+                    object.insert(key, operation["value"].clone());
+                }
+                "remove" => {
+                    object.remove(&key).unwrap();
+                }
+                op => panic!("Unexpected operation {op}"),
+            }
+        }
+        doc
+    }
+
+    #[test]
+    fn it_turns_old_image_into_new_image_by_patch() {
+        let old = item(json!({
+            "Id": { "N": "1" },
+            "Nickname": { "S": "JJ" },
+            "Profile": { "M": {
+                "Age": { "N": "35" },
+                "Address": { "M": { "City": { "S": "Tokyo" } } }
+            } }
+        }));
+        let new = item(json!({
+            "Id": { "N": "1" },
+            "Profile": { "M": {
+                "Age": { "N": "36" },
+                "Address": { "M": { "City": { "S": "Osaka" }, "Zip": { "S": "530" } } }
+            } },
+            "Tags": { "L": [{ "S": "a" }] }
+        }));
+        let operations = PatchOperation::diff(&old, &new);
+
+        let typed: Vec<Value> = operations
+            .iter()
+            .map(|op| serde_json::to_value(op).unwrap())
+            .collect();
+        assert_eq!(
+            apply(serde_json::to_value(&old).unwrap(), &typed),
+            serde_json::to_value(&new).unwrap()
+        );
+
+        let plain: Vec<Value> = operations
+            .iter()
+            .map(|op| op.to_plain_json().unwrap())
+            .collect();
+        assert_eq!(
+            apply(Value::Object(unmarshall_item(&old)), &plain),
+            Value::Object(unmarshall_item(&new))
+        );
+    }
+}
@@ -1,10 +1,15 @@
-use super::{Identity, OperationType, StreamRecord};
+use super::{AttributeValue, Identity, OperationType, RemovalReason, StreamRecord};
 
 use aws_sdk_dynamodbstreams::types;
-use serde::Serialize;
-use std::cmp::{Ord, Ordering, PartialOrd};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    cmp::{Ord, Ordering, PartialOrd},
+    collections::HashMap,
+};
 
-#[derive(Debug, Serialize, Clone, Eq, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct Record {
     event_id: Option<String>,
@@ -14,6 +19,9 @@ pub struct Record {
     aws_region: Option<String>,
     dynamodb: Option<StreamRecord>,
     user_identity: Option<Identity>,
+    /// Why the item was removed. Only `REMOVE` records have it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    removal_reason: Option<RemovalReason>,
 }
 
 #[cfg(test)]
@@ -27,24 +35,123 @@ impl Record {
             aws_region: None,
             dynamodb: None,
             user_identity: None,
+            removal_reason: None,
         }
     }
 
-    pub fn event_id(&self) -> &str {
-        self.event_id.as_ref().unwrap()
+    pub fn set_created_at(self, created_at: DateTime<Utc>) -> Self {
+        let dynamodb = self.dynamodb.unwrap_or_default().set_created_at(created_at);
+        Self {
+            dynamodb: Some(dynamodb),
+            ..self
+        }
+    }
+
+    pub fn set_size_bytes(self, size_bytes: i64) -> Self {
+        let dynamodb = self.dynamodb.unwrap_or_default().set_size_bytes(size_bytes);
+        Self {
+            dynamodb: Some(dynamodb),
+            ..self
+        }
+    }
+
+    pub fn set_sequence_number<T: Into<String>>(self, sequence_number: T) -> Self {
+        let dynamodb = self
+            .dynamodb
+            .unwrap_or_default()
+            .set_sequence_number(sequence_number);
+        Self {
+            dynamodb: Some(dynamodb),
+            ..self
+        }
+    }
+}
+
+impl Record {
+    pub fn event_id(&self) -> Option<&str> {
+        self.event_id.as_deref()
+    }
+
+    pub fn event_name(&self) -> Option<&OperationType> {
+        self.event_name.as_ref()
+    }
+
+    pub fn user_identity(&self) -> Option<&Identity> {
+        self.user_identity.as_ref()
+    }
+
+    pub fn removal_reason(&self) -> Option<RemovalReason> {
+        self.removal_reason
+    }
+
+    pub fn dynamodb(&self) -> Option<&StreamRecord> {
+        self.dynamodb.as_ref()
+    }
+
+    pub fn approximate_creation_date_time(&self) -> Option<&DateTime<Utc>> {
+        self.dynamodb
+            .as_ref()
+            .and_then(|r| r.approximate_creation_date_time())
+    }
+
+    pub fn sequence_number(&self) -> Option<&str> {
+        self.dynamodb.as_ref().and_then(|r| r.sequence_number())
+    }
+
+    /// The size of the record in bytes. It is 0 if unknown.
+    pub fn size_bytes(&self) -> u64 {
+        self.dynamodb
+            .as_ref()
+            .and_then(|r| r.size_bytes())
+            .map_or(0, |size| size.max(0) as u64)
+    }
+
+    /// Get `Keys`, `NewImage` and `OldImage` to modify.
+    pub fn images_mut(&mut self) -> impl Iterator<Item = &mut HashMap<String, AttributeValue>> {
+        self.dynamodb.iter_mut().flat_map(|r| r.images_mut())
+    }
+
+    /// Add the changes as JSON Patch to a `MODIFY` record.
+    pub fn set_diff(&mut self) {
+        if self.event_name != Some(OperationType::Modify) {
+            return;
+        }
+        if let Some(dynamodb) = self.dynamodb.as_mut() {
+            dynamodb.set_diff();
+        }
+    }
+
+    /// Serialize the record with the images in native JSON instead of the DynamoDB JSON.
+    pub fn to_plain_json(&self) -> serde_json::Result<Value> {
+        let mut value = serde_json::to_value(self)?;
+        if let Some(dynamodb) = self.dynamodb.as_ref() {
+            value["dynamodb"] = dynamodb.to_plain_json()?;
+        }
+        Ok(value)
     }
 }
 
 impl From<types::Record> for Record {
     fn from(value: types::Record) -> Record {
+        let event_name = value.event_name.map(OperationType::from);
+        let user_identity = value.user_identity.map(Identity::from);
+        let removal_reason = match (event_name.as_ref(), user_identity.as_ref()) {
+            (Some(OperationType::Remove), Some(identity)) if identity.is_ttl() => {
+                Some(RemovalReason::TtlExpiry)
+            }
+            (Some(OperationType::Remove), _) => Some(RemovalReason::UserDelete),
+            _ => None,
+        };
+
         Record {
             event_id: value.event_id,
-            event_name: value.event_name.map(OperationType::from),
+            event_name,
             event_version: value.event_version,
             event_source: value.event_source,
             aws_region: value.aws_region,
             dynamodb: value.dynamodb.map(StreamRecord::from),
-            user_identity: value.user_identity.map(Identity::from),
+            user_identity,
+            removal_reason,
         }
     }
 }
@@ -65,3 +172,41 @@ impl Ord for Record {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn remove(identity: Option<types::Identity>) -> Record {
+        let record = types::Record::builder()
+            .event_id("0")
+            .event_name(types::OperationType::Remove)
+            .set_user_identity(identity)
+            .build();
+        Record::from(record)
+    }
+
+    #[test]
+    fn it_derives_removal_reason_from_user_identity() {
+        let ttl = types::Identity::builder()
+            .principal_id("dynamodb.amazonaws.com")
+            .r#type("Service")
+            .build();
+        let record = remove(Some(ttl));
+        assert_eq!(record.removal_reason(), Some(RemovalReason::TtlExpiry));
+        assert_eq!(
+            serde_json::to_value(&record).unwrap()["removalReason"],
+            "TTL_EXPIRY"
+        );
+
+        assert_eq!(
+            remove(None).removal_reason(),
+            Some(RemovalReason::UserDelete)
+        );
+
+        let insert = types::Record::builder()
+            .event_name(types::OperationType::Insert)
+            .build();
+        assert_eq!(Record::from(insert).removal_reason(), None);
+    }
+}
@@ -1,8 +1,10 @@
 use super::Record;
 
-use serde::Serialize;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 
-#[derive(Debug, Default, Serialize, Clone)]
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
 #[serde(rename_all = "PascalCase")]
 pub struct Records {
     records: Vec<Record>,
@@ -21,11 +23,46 @@ impl Records {
         self.records.append(&mut records.records)
     }
 
-    pub fn sort(&mut self) {
-        self.records.sort()
+    pub fn into_inner(self) -> Vec<Record> {
+        self.records
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Record> {
+        self.records.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Record> {
+        self.records.iter_mut()
+    }
+
+    /// Retain only the records specified by the predicate.
+    pub fn retain<F: FnMut(&Record) -> bool>(&mut self, f: F) {
+        self.records.retain(f)
+    }
+
+    /// Get the sequence number of the last record.
+    pub fn last_sequence_number(&self) -> Option<&str> {
+        self.records.iter().rev().find_map(|r| r.sequence_number())
+    }
+
+    /// Get the creation time of the newest record.
+    pub fn latest_created_at(&self) -> Option<&DateTime<Utc>> {
+        self.records
+            .iter()
+            .filter_map(|r| r.approximate_creation_date_time())
+            .max()
+    }
+
+    /// Serialize the records with the images in native JSON instead of the DynamoDB JSON.
+    pub fn to_plain_json(&self) -> serde_json::Result<Value> {
+        let records = self
+            .records
+            .iter()
+            .map(Record::to_plain_json)
+            .collect::<serde_json::Result<Vec<_>>>()?;
+        Ok(json!({ "Records": records }))
     }
 
-    #[cfg(test)]
     pub fn len(&self) -> usize {
         self.records.len()
     }
@@ -35,7 +72,7 @@ impl Records {
         let event_id: String = event_id.into();
         self.records
             .iter()
-            .find(|r| r.event_id() == event_id.as_str())
+            .find(|r| r.event_id() == Some(event_id.as_str()))
             .is_some()
     }
 }
@@ -50,3 +87,57 @@ where
         Records { records }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_appends_records() {
+        let mut records = Records::from(vec![Record::new("0"), Record::new("1")]);
+        let mut others = Records::from(vec![Record::new("2")]);
+
+        records.append(&mut others);
+
+        assert_eq!(records.len(), 3);
+        assert!(records.includes("0"));
+        assert!(records.includes("2"));
+        assert!(others.is_empty());
+    }
+
+    #[test]
+    fn it_reads_back_serialized_records() {
+        let payload = json!({
+            "Records": [{
+                "eventId": "c4ca4238a0b923820dcc509a6f75849b",
+                "eventName": "MODIFY",
+                "eventVersion": "1.1",
+                "eventSource": "aws:dynamodb",
+                "awsRegion": "us-east-1",
+                "dynamodb": {
+                    "ApproximateCreationDateTime": "2023-11-20T00:00:00Z",
+                    "Keys": { "Id": { "N": "101" } },
+                    "NewImage": {
+                        "Id": { "N": "101" },
+                        "Avatar": { "B": "/wD+" },
+                        "Tags": { "SS": ["a", "b"] },
+                        "Profile": { "M": { "Age": { "N": "35" }, "Deleted": { "NULL": true } } },
+                        "History": { "L": [{ "BOOL": true }, { "BS": ["AQI="] }] }
+                    },
+                    "OldImage": null,
+                    "SequenceNumber": "222",
+                    "SizeBytes": 59,
+                    "StreamViewType": "NEW_AND_OLD_IMAGES"
+                },
+                "userIdentity": {
+                    "principalId": "dynamodb.amazonaws.com",
+                    "type": "Service"
+                }
+            }]
+        });
+
+        let records: Records = serde_json::from_value(payload.clone()).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(serde_json::to_value(&records).unwrap(), payload);
+    }
+}
@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+/// Why an item was removed.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Eq, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RemovalReason {
+    /// Deleted by a user or an application.
+    UserDelete,
+    /// Deleted by DynamoDB because the TTL of the item expired.
+    TtlExpiry,
+}
+
+impl RemovalReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::UserDelete => "USER_DELETE",
+            Self::TtlExpiry => "TTL_EXPIRY",
+        }
+    }
+}
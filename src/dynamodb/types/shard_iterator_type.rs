@@ -1,7 +1,7 @@
 use aws_sdk_dynamodbstreams::types;
 use serde::Serialize;
 
-#[derive(Debug, Serialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Clone, Eq, PartialEq)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ShardIteratorType {
     AfterSequenceNumber,
@@ -22,3 +22,15 @@ impl From<types::ShardIteratorType> for ShardIteratorType {
         }
     }
 }
+
+impl From<ShardIteratorType> for types::ShardIteratorType {
+    fn from(value: ShardIteratorType) -> types::ShardIteratorType {
+        match value {
+            ShardIteratorType::AfterSequenceNumber => types::ShardIteratorType::AfterSequenceNumber,
+            ShardIteratorType::AtSequenceNumber => types::ShardIteratorType::AtSequenceNumber,
+            ShardIteratorType::Latest => types::ShardIteratorType::Latest,
+            ShardIteratorType::TrimHorizon => types::ShardIteratorType::TrimHorizon,
+            ShardIteratorType::Unknown => types::ShardIteratorType::from("UNKNOWN"),
+        }
+    }
+}
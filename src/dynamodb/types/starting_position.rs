@@ -0,0 +1,83 @@
+use super::Record;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// The position in the stream where a subscription starts reading.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum StartingPosition {
+    /// Start reading just after the most recent record in each shard.
+    #[default]
+    Latest,
+    /// Start reading at the oldest record in each shard (within the 24h stream window).
+    TrimHorizon,
+    /// Start reading at the sequence number in the shard. The shards derived from it start at
+    /// their oldest records and the other shards start at their latest ones.
+    AtSequenceNumber {
+        shard_id: String,
+        sequence_number: String,
+    },
+    /// Start reading at the oldest record in each shard and skip records created before the
+    /// timestamp.
+    AtTimestamp(DateTime<Utc>),
+}
+
+impl StartingPosition {
+    /// Check if the record should be delivered from this starting position.
+    pub fn includes(&self, record: &Record) -> bool {
+        match self {
+            Self::AtTimestamp(timestamp) => record
+                .approximate_creation_date_time()
+                .map(|created_at| created_at >= timestamp)
+                .unwrap_or(true),
+            _ => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_deserializes_starting_positions() {
+        let position: StartingPosition = serde_json::from_str("\"TRIM_HORIZON\"").unwrap();
+        assert_eq!(position, StartingPosition::TrimHorizon);
+
+        let position: StartingPosition = serde_json::from_value(serde_json::json!({
+            "AT_SEQUENCE_NUMBER": {
+                "shard_id": "shardId-0001",
+                "sequence_number": "100"
+            }
+        }))
+        .unwrap();
+        assert_eq!(
+            position,
+            StartingPosition::AtSequenceNumber {
+                shard_id: "shardId-0001".into(),
+                sequence_number: "100".into(),
+            }
+        );
+
+        let position: StartingPosition = serde_json::from_value(serde_json::json!({
+            "AT_TIMESTAMP": "2023-11-20T00:00:00Z"
+        }))
+        .unwrap();
+        let expected = DateTime::<Utc>::from_timestamp(1_700_438_400, 0).unwrap();
+        assert_eq!(position, StartingPosition::AtTimestamp(expected));
+    }
+
+    #[test]
+    fn it_skips_records_older_than_the_timestamp() {
+        let timestamp = DateTime::<Utc>::from_timestamp(1_700_438_400, 0).unwrap();
+        let position = StartingPosition::AtTimestamp(timestamp);
+
+        let older = Record::new("0").set_created_at(timestamp - chrono::Duration::seconds(1));
+        let newer = Record::new("1").set_created_at(timestamp);
+
+        assert!(!position.includes(&older));
+        assert!(position.includes(&newer));
+        assert!(StartingPosition::Latest.includes(&older));
+    }
+}
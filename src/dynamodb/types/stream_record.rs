@@ -1,14 +1,18 @@
-use super::{into_chrono, into_item, AttributeValue, StreamViewType};
+use super::{
+    attribute_value::unmarshall_item, into_chrono, into_item, AttributeValue, PatchOperation,
+    StreamViewType,
+};
 
 use aws_sdk_dynamodbstreams::types;
 use chrono::{DateTime, Utc};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::{
     cmp::{Ord, Ordering, PartialOrd},
     collections::HashMap,
 };
 
-#[derive(Debug, Serialize, Clone, Eq, PartialEq)]
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Eq, PartialEq)]
 #[serde(rename_all = "PascalCase")]
 pub struct StreamRecord {
     approximate_creation_date_time: Option<DateTime<Utc>>,
@@ -18,6 +22,83 @@ pub struct StreamRecord {
     sequence_number: Option<String>,
     size_bytes: Option<i64>,
     stream_view_type: Option<StreamViewType>,
+    /// The changes from the old image to the new one. It is computed only on demand.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    diff: Option<Vec<PatchOperation>>,
+}
+
+impl StreamRecord {
+    pub fn approximate_creation_date_time(&self) -> Option<&DateTime<Utc>> {
+        self.approximate_creation_date_time.as_ref()
+    }
+
+    pub fn sequence_number(&self) -> Option<&str> {
+        self.sequence_number.as_deref()
+    }
+
+    pub fn size_bytes(&self) -> Option<i64> {
+        self.size_bytes
+    }
+
+    /// Get `Keys`, `NewImage` and `OldImage` to modify.
+    pub fn images_mut(&mut self) -> impl Iterator<Item = &mut HashMap<String, AttributeValue>> {
+        [&mut self.keys, &mut self.new_image, &mut self.old_image]
+            .into_iter()
+            .flatten()
+    }
+
+    /// Compute the changes if the record has both of the images.
+    pub fn set_diff(&mut self) {
+        if let (Some(old), Some(new)) = (self.old_image.as_ref(), self.new_image.as_ref()) {
+            self.diff = Some(PatchOperation::diff(old, new));
+        }
+    }
+
+    /// Serialize the record with `Keys`, `NewImage` and `OldImage` in native JSON.
+    pub fn to_plain_json(&self) -> serde_json::Result<Value> {
+        let mut value = serde_json::to_value(self)?;
+        let images = [
+            ("Keys", &self.keys),
+            ("NewImage", &self.new_image),
+            ("OldImage", &self.old_image),
+        ];
+        for (key, image) in images {
+            if let Some(image) = image {
+                value[key] = Value::Object(unmarshall_item(image));
+            }
+        }
+        if let Some(diff) = self.diff.as_ref() {
+            value["Diff"] = diff
+                .iter()
+                .map(PatchOperation::to_plain_json)
+                .collect::<serde_json::Result<_>>()?;
+        }
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+impl StreamRecord {
+    pub fn set_created_at(self, created_at: DateTime<Utc>) -> Self {
+        Self {
+            approximate_creation_date_time: Some(created_at),
+            ..self
+        }
+    }
+
+    pub fn set_size_bytes(self, size_bytes: i64) -> Self {
+        Self {
+            size_bytes: Some(size_bytes),
+            ..self
+        }
+    }
+
+    pub fn set_sequence_number<T: Into<String>>(self, sequence_number: T) -> Self {
+        Self {
+            sequence_number: Some(sequence_number.into()),
+            ..self
+        }
+    }
 }
 
 impl From<types::StreamRecord> for StreamRecord {
@@ -30,6 +111,7 @@ impl From<types::StreamRecord> for StreamRecord {
             sequence_number: value.sequence_number,
             size_bytes: value.size_bytes,
             stream_view_type: value.stream_view_type.map(StreamViewType::from),
+            diff: None,
         }
     }
 }
@@ -1,7 +1,15 @@
 mod channel;
 mod dynamodb;
+mod metrics;
 pub mod web;
 
 pub const ENV_DYNAMODB_ENDPOINT_URL: &str = "DYNAMODB_ENDPOINT_URL";
 pub const ENV_PORT: &str = "PORT";
 pub const ENV_CONFIG_PATH: &str = "CONFIG_PATH";
+pub const ENV_CHECKPOINT_PATH: &str = "CHECKPOINT_PATH";
+pub const ENV_DEAD_LETTER_PATH: &str = "DEAD_LETTER_PATH";
+pub const ENV_MAX_RESTARTS: &str = "MAX_RESTARTS";
+pub const ENV_SHUTDOWN_TIMEOUT: &str = "SHUTDOWN_TIMEOUT";
+pub const ENV_DEDUP_WINDOW: &str = "DEDUP_WINDOW";
+pub const ENV_DEDUP_CAPACITY: &str = "DEDUP_CAPACITY";
+pub const ENV_DEDUP_PATH: &str = "DEDUP_PATH";
@@ -1,4 +1,4 @@
-use dynamo_stream::web::{route::root, AppState, Config, SharedState};
+use dynamo_stream::web::{route::root, shutdown, AppState, Config, SharedState};
 use std::net::SocketAddr;
 use tower_http::{
     trace::{DefaultMakeSpan, DefaultOnRequest, DefaultOnResponse, TraceLayer},
@@ -9,13 +9,16 @@ use tracing_subscriber::FmtSubscriber;
 
 #[tokio::main]
 async fn main() {
-    let subscriber = FmtSubscriber::new();
+    // Logs go to stderr so that they don't mix with the records of stdout sinks.
+    let subscriber = FmtSubscriber::builder()
+        .with_writer(std::io::stderr)
+        .finish();
     tracing::subscriber::set_global_default(subscriber).expect("setting default subscriber failed");
 
     let config = Config::new();
     let state: SharedState = AppState::new(&config).await.into();
 
-    let app = root::router(state).layer(
+    let app = root::router(state.clone()).layer(
         TraceLayer::new_for_http()
             .make_span_with(
                 DefaultMakeSpan::new()
@@ -36,6 +39,37 @@ async fn main() {
 
     axum::Server::bind(&addr)
         .serve(app.into_make_service())
+        .with_graceful_shutdown(shutdown_signal())
         .await
         .unwrap();
+
+    // The server doesn't accept requests anymore. Stop streams and listeners.
+    shutdown(state, config.shutdown_timeout()).await;
+    info!("shut down");
+}
+
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to install signal handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+
+    info!("signal received, starting graceful shutdown");
 }
@@ -0,0 +1,188 @@
+use prometheus::{
+    Encoder, GaugeVec, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts, Registry,
+    TextEncoder,
+};
+use std::sync::OnceLock;
+
+const NAMESPACE: &str = "dynamo_stream";
+
+/// The Prometheus metrics of the process.
+pub struct Metrics {
+    registry: Registry,
+    /// GetRecords calls per table.
+    pub get_records: IntCounterVec,
+    /// Failed GetRecords calls per table.
+    pub get_records_errors: IntCounterVec,
+    /// DescribeStream calls per table.
+    pub describe_stream: IntCounterVec,
+    /// Failed DescribeStream calls per table.
+    pub describe_stream_errors: IntCounterVec,
+    /// Records read per table and shard.
+    pub records_read: IntCounterVec,
+    /// Records dropped as duplicates per table.
+    pub duplicates_suppressed: IntCounterVec,
+    /// Records delivered per table and destination.
+    pub records_delivered: IntCounterVec,
+    /// Records which could not be delivered per table and destination.
+    pub records_failed: IntCounterVec,
+    /// Records dropped by the transform per table and destination.
+    pub records_dropped: IntCounterVec,
+    /// Time to deliver a batch including retries per table and destination.
+    pub delivery_duration: HistogramVec,
+    /// How far the newest record read in the last poll was behind per table.
+    pub iterator_age: GaugeVec,
+    pub subscriptions: IntGauge,
+    pub listeners: IntGauge,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new();
+
+        let counter = |name: &str, help: &str, labels: &[&str]| {
+            let counter = IntCounterVec::new(Opts::new(name, help).namespace(NAMESPACE), labels)
+                .expect("Invalid counter");
+            registry
+                .register(Box::new(counter.clone()))
+                .expect("Failed to register counter");
+            counter
+        };
+
+        let gauge = |name: &str, help: &str| {
+            let gauge = IntGauge::with_opts(Opts::new(name, help).namespace(NAMESPACE))
+                .expect("Invalid gauge");
+            registry
+                .register(Box::new(gauge.clone()))
+                .expect("Failed to register gauge");
+            gauge
+        };
+
+        let get_records = counter(
+            "get_records_total",
+            "The number of GetRecords calls.",
+            &["table"],
+        );
+        let get_records_errors = counter(
+            "get_records_errors_total",
+            "The number of failed GetRecords calls.",
+            &["table"],
+        );
+        let describe_stream = counter(
+            "describe_stream_total",
+            "The number of DescribeStream calls.",
+            &["table"],
+        );
+        let describe_stream_errors = counter(
+            "describe_stream_errors_total",
+            "The number of failed DescribeStream calls.",
+            &["table"],
+        );
+        let records_read = counter(
+            "records_read_total",
+            "The number of records read from the shard.",
+            &["table", "shard"],
+        );
+        let duplicates_suppressed = counter(
+            "duplicates_suppressed_total",
+            "The number of records dropped because they had been delivered.",
+            &["table"],
+        );
+        let records_delivered = counter(
+            "records_delivered_total",
+            "The number of records delivered to the destination.",
+            &["table", "destination"],
+        );
+        let records_failed = counter(
+            "records_failed_total",
+            "The number of records which could not be delivered to the destination.",
+            &["table", "destination"],
+        );
+        let records_dropped = counter(
+            "records_dropped_total",
+            "The number of records which the transform dropped for the destination.",
+            &["table", "destination"],
+        );
+
+        let delivery_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "delivery_duration_seconds",
+                "The time to deliver records to the destination including retries.",
+            )
+            .namespace(NAMESPACE),
+            &["table", "destination"],
+        )
+        .expect("Invalid histogram");
+        registry
+            .register(Box::new(delivery_duration.clone()))
+            .expect("Failed to register histogram");
+
+        let iterator_age = GaugeVec::new(
+            Opts::new(
+                "iterator_age_seconds",
+                "How far the newest record read in the last poll was behind.",
+            )
+            .namespace(NAMESPACE),
+            &["table"],
+        )
+        .expect("Invalid gauge");
+        registry
+            .register(Box::new(iterator_age.clone()))
+            .expect("Failed to register gauge");
+
+        let subscriptions = gauge("subscriptions", "The number of active subscriptions.");
+        let listeners = gauge("listeners", "The number of active listeners.");
+
+        Self {
+            registry,
+            get_records,
+            get_records_errors,
+            describe_stream,
+            describe_stream_errors,
+            records_read,
+            duplicates_suppressed,
+            records_delivered,
+            records_failed,
+            records_dropped,
+            delivery_duration,
+            iterator_age,
+            subscriptions,
+            listeners,
+        }
+    }
+
+    /// Encode the metrics in the Prometheus text format.
+    pub fn encode(&self) -> Result<String, prometheus::Error> {
+        let mut buf: Vec<u8> = vec![];
+        TextEncoder::new().encode(&self.registry.gather(), &mut buf)?;
+        Ok(String::from_utf8_lossy(&buf).into_owned())
+    }
+}
+
+/// Get the metrics shared across the process.
+pub fn metrics() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(Metrics::new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_encodes_metrics_in_text_format() {
+        let metrics = Metrics::new();
+        metrics.get_records.with_label_values(&["People"]).inc();
+        metrics
+            .records_delivered
+            .with_label_values(&["People", "01HFVQS31XVYF5S6BFWTBTCQ6S"])
+            .inc_by(3);
+        metrics.subscriptions.set(1);
+
+        let text = metrics.encode().unwrap();
+        assert!(text.contains("dynamo_stream_get_records_total{table=\"People\"} 1"));
+        assert!(text.contains(
+            "dynamo_stream_records_delivered_total{destination=\"01HFVQS31XVYF5S6BFWTBTCQ6S\",table=\"People\"} 3"
+        ));
+        assert!(text.contains("dynamo_stream_subscriptions 1"));
+    }
+}
@@ -1,3 +1,5 @@
+use super::{ListenerConfig, SinkConfig, StartingPosition};
+
 use serde::Deserialize;
 use std::fs;
 use std::path::Path;
@@ -9,9 +11,41 @@ pub struct ConfigFile {
 }
 
 #[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(try_from = "RawEntry")]
 pub struct Entry {
     pub table_name: String,
-    pub url: String,
+    /// The ID of the destination. It is derived from the table and the sink when omitted.
+    pub id: Option<String>,
+    pub sink: SinkConfig,
+    pub starting_position: StartingPosition,
+    pub listener: ListenerConfig,
+}
+
+/// An entry has either `url` of an HTTP endpoint or `sink` as its destination.
+#[derive(Debug, Deserialize)]
+struct RawEntry {
+    table_name: String,
+    id: Option<String>,
+    url: Option<String>,
+    sink: Option<SinkConfig>,
+    #[serde(default, with = "serde_yaml::with::singleton_map")]
+    starting_position: StartingPosition,
+    #[serde(flatten)]
+    listener: ListenerConfig,
+}
+
+impl TryFrom<RawEntry> for Entry {
+    type Error = String;
+
+    fn try_from(raw: RawEntry) -> Result<Self, Self::Error> {
+        Ok(Self {
+            sink: SinkConfig::resolve(raw.url, raw.sink)?,
+            table_name: raw.table_name,
+            id: raw.id,
+            starting_position: raw.starting_position,
+            listener: raw.listener,
+        })
+    }
 }
 
 impl ConfigFile {
@@ -42,6 +76,13 @@ fn _read_config<P: AsRef<Path>>(path: P) -> Result<ConfigFile, String> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::channel::OverflowPolicy;
+    use crate::web::listener::{BatchConfig, PayloadFormat, Projection, RetryConfig, TtlDeletions};
+    use chrono::{DateTime, Utc};
+
+    fn http(url: &str) -> SinkConfig {
+        SinkConfig::resolve(Some(url.into()), None).unwrap()
+    }
 
     #[test]
     fn it_loads_config() {
@@ -53,10 +94,13 @@ mod tests {
         assert_eq!(config.entries().len(), 2);
 
         assert_eq!(
-            config.entries().get(0).unwrap(),
+            config.entries().first().unwrap(),
             &Entry {
                 table_name: "People".into(),
-                url: "http://localhost:8888".into(),
+                id: None,
+                sink: http("http://localhost:8888"),
+                starting_position: StartingPosition::Latest,
+                listener: ListenerConfig::default(),
             }
         );
 
@@ -64,7 +108,45 @@ mod tests {
             config.entries().get(1).unwrap(),
             &Entry {
                 table_name: "User".into(),
-                url: "http://localhost:4000".into(),
+                id: Some("user-webhook".into()),
+                sink: http("http://localhost:4000"),
+                starting_position: StartingPosition::AtTimestamp(
+                    DateTime::<Utc>::from_timestamp(1_700_438_400, 0).unwrap()
+                ),
+                listener: ListenerConfig {
+                    buffer_size: 4,
+                    overflow_policy: OverflowPolicy::DropNewest,
+                    retry: RetryConfig {
+                        max_attempts: 3,
+                        retryable_status_codes: Some(vec![500, 503]),
+                        ..RetryConfig::default()
+                    },
+                    filters: serde_json::from_value(serde_json::json!([{
+                        "eventName": ["INSERT"],
+                        "dynamodb": {
+                            "NewImage": { "Age": { "N": [{ "numeric": [">=", 20] }] } }
+                        }
+                    }]))
+                    .unwrap(),
+                    projection: Projection {
+                        include: Some(vec!["Id".into(), "Email".into()]),
+                        exclude: vec![],
+                        rename: [("Email".into(), "email_address".into())].into(),
+                    },
+                    ttl: TtlDeletions::Exclude,
+                    format: PayloadFormat::PlainJson,
+                    diff: true,
+                    transform: serde_json::from_value(serde_json::json!({
+                        "expression": "{ id: .dynamodb.Keys.Id }",
+                        "scope": "RECORD"
+                    }))
+                    .unwrap(),
+                    batch: BatchConfig {
+                        max_batch_records: Some(100),
+                        max_batch_bytes: None,
+                        max_batching_window_ms: Some(5_000),
+                    },
+                },
             }
         );
     }
@@ -89,7 +171,7 @@ mod tests {
         let message = result.unwrap_err();
         assert_eq!(
             message,
-            "Failed to deserialize config file: entries[0]: missing field `url` at line 2 column 5"
+            "Failed to deserialize config file: entries: missing field `url` or `sink` at line 2 column 3"
         );
     }
 }
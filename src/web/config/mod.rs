@@ -1,15 +1,27 @@
 mod file;
 
-use super::{ENV_CONFIG_PATH, ENV_DYNAMODB_ENDPOINT_URL, ENV_PORT};
+use super::{
+    listener::SinkConfig, DedupConfig, ListenerConfig, RestartConfig, StartingPosition,
+    ENV_CHECKPOINT_PATH, ENV_CONFIG_PATH, ENV_DEAD_LETTER_PATH, ENV_DEDUP_CAPACITY, ENV_DEDUP_PATH,
+    ENV_DEDUP_WINDOW, ENV_DYNAMODB_ENDPOINT_URL, ENV_MAX_RESTARTS, ENV_PORT, ENV_SHUTDOWN_TIMEOUT,
+};
 
-use std::env;
+use std::{env, time::Duration};
 
-use file::{ConfigFile, Entry};
+use file::ConfigFile;
+
+pub use file::Entry;
 
 #[derive(Debug)]
 pub struct Config {
     endpoint_url: Option<String>,
     port: u16,
+    checkpoint_path: Option<String>,
+    dead_letter_path: Option<String>,
+    restart: RestartConfig,
+    shutdown_timeout: Duration,
+    dedup: Option<DedupConfig>,
+    dedup_path: Option<String>,
     entries: Vec<Entry>,
 }
 
@@ -21,12 +33,51 @@ impl Config {
             .and_then(|p| p.parse::<u16>().ok())
             .unwrap_or(3000);
 
+        let checkpoint_path = env::var(ENV_CHECKPOINT_PATH).ok();
+        let dead_letter_path = env::var(ENV_DEAD_LETTER_PATH).ok();
+
+        let mut restart = RestartConfig::default();
+        if let Some(max_restarts) = env::var(ENV_MAX_RESTARTS)
+            .ok()
+            .and_then(|n| n.parse::<u32>().ok())
+        {
+            restart.max_restarts = max_restarts;
+        }
+
+        let shutdown_timeout = env::var(ENV_SHUTDOWN_TIMEOUT)
+            .ok()
+            .and_then(|t| t.parse::<u64>().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(Duration::from_secs(30));
+
+        // Deduplication is enabled only when the window is set.
+        let dedup = env::var(ENV_DEDUP_WINDOW)
+            .ok()
+            .and_then(|t| t.parse::<u64>().ok())
+            .map(|secs| {
+                let mut dedup = DedupConfig::new(Duration::from_secs(secs));
+                if let Some(capacity) = env::var(ENV_DEDUP_CAPACITY)
+                    .ok()
+                    .and_then(|n| n.parse::<usize>().ok())
+                {
+                    dedup.capacity = capacity;
+                }
+                dedup
+            });
+        let dedup_path = env::var(ENV_DEDUP_PATH).ok();
+
         let conf_path = env::var(ENV_CONFIG_PATH).ok();
         let file = ConfigFile::new(conf_path);
 
         Self {
             endpoint_url,
             port,
+            checkpoint_path,
+            dead_letter_path,
+            restart,
+            shutdown_timeout,
+            dedup,
+            dedup_path,
             entries: file.entries(),
         }
     }
@@ -39,6 +90,31 @@ impl Config {
         self.port
     }
 
+    pub fn checkpoint_path(&self) -> Option<String> {
+        self.checkpoint_path.clone()
+    }
+
+    pub fn dead_letter_path(&self) -> Option<String> {
+        self.dead_letter_path.clone()
+    }
+
+    pub fn restart(&self) -> RestartConfig {
+        self.restart.clone()
+    }
+
+    /// How long to wait for streams and listeners to stop on shutdown.
+    pub fn shutdown_timeout(&self) -> Duration {
+        self.shutdown_timeout
+    }
+
+    pub fn dedup(&self) -> Option<DedupConfig> {
+        self.dedup.clone()
+    }
+
+    pub fn dedup_path(&self) -> Option<String> {
+        self.dedup_path.clone()
+    }
+
     pub fn entries(&self) -> Vec<Entry> {
         self.entries.clone()
     }
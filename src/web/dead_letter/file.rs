@@ -0,0 +1,166 @@
+use super::{DeadLetter, DeadLetterStore};
+
+use anyhow::Result;
+use axum::async_trait;
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
+use tokio::{
+    fs::{self, OpenOptions},
+    io::AsyncWriteExt,
+    sync::Mutex,
+};
+use tracing::warn;
+
+/// A dead-letter store appending each dead letter to a JSONL file.
+/// The file is rewritten only when dead letters are removed.
+#[derive(Debug)]
+pub struct FileDeadLetterStore {
+    path: PathBuf,
+    letters: Mutex<Vec<DeadLetter>>,
+}
+
+impl FileDeadLetterStore {
+    /// Open the dead-letter file. A missing file is treated as an empty one.
+    pub async fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+
+        let letters = match fs::read_to_string(&path).await {
+            Ok(content) => content
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .filter_map(|line| {
+                    serde_json::from_str::<DeadLetter>(line)
+                        .map_err(|err| warn!("Skip broken dead letter: {err}"))
+                        .ok()
+                })
+                .collect(),
+            Err(err) if err.kind() == ErrorKind::NotFound => vec![],
+            Err(err) => return Err(anyhow::Error::from(err)),
+        };
+
+        Ok(Self {
+            path,
+            letters: Mutex::new(letters),
+        })
+    }
+}
+
+#[async_trait]
+impl DeadLetterStore for FileDeadLetterStore {
+    async fn push(&self, letter: DeadLetter) -> Result<()> {
+        let mut letters = self.letters.lock().await;
+
+        let mut line = serde_json::to_vec(&letter)?;
+        line.push(b'\n');
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        file.write_all(&line).await?;
+        file.flush().await?;
+
+        letters.push(letter);
+        Ok(())
+    }
+
+    async fn list(&self, destination_id: &str) -> Result<Vec<DeadLetter>> {
+        let letters = self.letters.lock().await;
+        Ok(letters
+            .iter()
+            .filter(|l| l.destination_id == destination_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn get(&self, destination_id: &str, id: &str) -> Result<Option<DeadLetter>> {
+        let letters = self.letters.lock().await;
+        Ok(letters
+            .iter()
+            .find(|l| l.destination_id == destination_id && l.id == id)
+            .cloned())
+    }
+
+    async fn remove(&self, destination_id: &str, ids: Option<&[String]>) -> Result<()> {
+        let mut letters = self.letters.lock().await;
+
+        letters.retain(|l| {
+            l.destination_id != destination_id
+                || ids.map(|ids| !ids.contains(&l.id)).unwrap_or(false)
+        });
+
+        let mut content: Vec<u8> = vec![];
+        for letter in letters.iter() {
+            content.append(&mut serde_json::to_vec(letter)?);
+            content.push(b'\n');
+        }
+
+        // Write to a temporary file first and rename it not to leave a broken file. The suffix
+        // is added to the whole file name so that files sharing a stem don't share it.
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        fs::write(&tmp, content).await?;
+        fs::rename(&tmp, &self.path).await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use ulid::Ulid;
+
+    fn letter(id: &str, destination_id: &str) -> DeadLetter {
+        DeadLetter {
+            id: id.into(),
+            table: "People".into(),
+            destination_id: destination_id.into(),
+            error: "The response status is 500 Internal Server Error".into(),
+            status: Some(500),
+            attempts: 5,
+            failed_at: Utc::now(),
+            // A CloudEvents event has a record without `Records`.
+            records: 1,
+            untransformed: false,
+            headers: Default::default(),
+            payload: serde_json::json!({ "specversion": "1.0", "data": {} }),
+        }
+    }
+
+    #[tokio::test]
+    async fn it_keeps_dead_letters_per_destination() {
+        let path = std::env::temp_dir().join(format!("{}.jsonl", Ulid::new()));
+
+        let store = FileDeadLetterStore::open(&path).await.unwrap();
+        store.push(letter("0", "dest-a")).await.unwrap();
+        store.push(letter("1", "dest-a")).await.unwrap();
+        store.push(letter("2", "dest-b")).await.unwrap();
+
+        // Reopen the file to confirm that the dead letters are persisted.
+        let store = FileDeadLetterStore::open(&path).await.unwrap();
+        let ids = |letters: Vec<DeadLetter>| -> Vec<String> {
+            letters.into_iter().map(|l| l.id).collect()
+        };
+        assert_eq!(ids(store.list("dest-a").await.unwrap()), vec!["0", "1"]);
+        assert_eq!(ids(store.list("dest-b").await.unwrap()), vec!["2"]);
+        assert!(store.get("dest-a", "1").await.unwrap().is_some());
+        assert!(store.get("dest-b", "1").await.unwrap().is_none());
+
+        store.remove("dest-a", Some(&["0".into()])).await.unwrap();
+        assert_eq!(ids(store.list("dest-a").await.unwrap()), vec!["1"]);
+
+        store.remove("dest-a", None).await.unwrap();
+        assert!(store.list("dest-a").await.unwrap().is_empty());
+
+        let store = FileDeadLetterStore::open(&path).await.unwrap();
+        assert!(store.list("dest-a").await.unwrap().is_empty());
+        assert_eq!(ids(store.list("dest-b").await.unwrap()), vec!["2"]);
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
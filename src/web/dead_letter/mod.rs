@@ -0,0 +1,47 @@
+mod file;
+
+use anyhow::Result;
+use axum::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+pub use file::FileDeadLetterStore;
+
+/// A records batch that could not be delivered to the destination.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub id: String,
+    pub table: String,
+    pub destination_id: String,
+    pub error: String,
+    pub status: Option<u16>,
+    pub attempts: u32,
+    pub failed_at: DateTime<Utc>,
+    /// The number of records in the payload.
+    #[serde(default)]
+    pub records: usize,
+    /// The transform failed on the payload, so it is transformed again when replayed.
+    #[serde(default)]
+    pub untransformed: bool,
+    /// The headers which were sent with the payload.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// The JSON payload which was sent to the destination.
+    pub payload: serde_json::Value,
+}
+
+#[async_trait]
+pub trait DeadLetterStore: Send + Sync {
+    /// Keep the dead letter.
+    async fn push(&self, letter: DeadLetter) -> Result<()>;
+
+    /// Get dead letters of the destination in the order they failed.
+    async fn list(&self, destination_id: &str) -> Result<Vec<DeadLetter>>;
+
+    /// Get the dead letter of the destination.
+    async fn get(&self, destination_id: &str, id: &str) -> Result<Option<DeadLetter>>;
+
+    /// Remove the dead letters of the destination. If `ids` is None, remove all of them.
+    async fn remove(&self, destination_id: &str, ids: Option<&[String]>) -> Result<()>;
+}
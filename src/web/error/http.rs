@@ -13,6 +13,8 @@ pub enum HttpError {
     Validation(ValidationErrors),
     #[error("Unprocessable: `{0}`")]
     Unprocessable(String),
+    #[error("Not found: `{0}`")]
+    NotFound(String),
     #[error("Internal server error")]
     Server(#[from] anyhow::Error),
 }
@@ -40,6 +42,7 @@ impl HttpError {
         match self {
             Self::Validation(_) => StatusCode::BAD_REQUEST,
             Self::Unprocessable(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Self::NotFound(_) => StatusCode::NOT_FOUND,
             Self::Server(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -0,0 +1,207 @@
+use super::{Ack, Record, Records};
+
+use serde::Deserialize;
+use std::mem;
+use tokio::time::{Duration, Instant};
+
+/// The batching options for each destination. Without any of them, the records of each poll
+/// are delivered at once.
+#[derive(Debug, Default, Clone, Deserialize, PartialEq)]
+pub struct BatchConfig {
+    /// The maximum number of records in a request.
+    pub max_batch_records: Option<usize>,
+    /// The maximum total of `SizeBytes` of the records in a request. A record larger than
+    /// this is delivered alone.
+    pub max_batch_bytes: Option<u64>,
+    /// How long records can be held to gather a larger batch across polls.
+    pub max_batching_window_ms: Option<u64>,
+}
+
+impl BatchConfig {
+    pub fn window(&self) -> Option<Duration> {
+        self.max_batching_window_ms.map(Duration::from_millis)
+    }
+
+    fn is_full(&self, count: usize, bytes: u64) -> bool {
+        self.max_batch_records.is_some_and(|max| count >= max)
+            || self.max_batch_bytes.is_some_and(|max| bytes >= max)
+    }
+
+    fn exceeds(&self, count: usize, bytes: u64) -> bool {
+        self.max_batch_records.is_some_and(|max| count > max)
+            || self.max_batch_bytes.is_some_and(|max| bytes > max)
+    }
+
+    /// Split the records into batches within the limits keeping their order.
+    pub fn split(&self, records: Records) -> Vec<Records> {
+        let mut batches: Vec<Records> = vec![];
+        let mut batch: Vec<Record> = vec![];
+        let mut bytes: u64 = 0;
+
+        for record in records.into_inner() {
+            let size = record.size_bytes();
+            if !batch.is_empty() && self.exceeds(batch.len() + 1, bytes + size) {
+                batches.push(Records::from(mem::take(&mut batch)));
+                bytes = 0;
+            }
+            bytes += size;
+            batch.push(record);
+        }
+
+        if !batch.is_empty() {
+            batches.push(Records::from(batch));
+        }
+        batches
+    }
+}
+
+/// Records held by a listener until a batch is full or the batching window has passed.
+#[derive(Debug, Default)]
+pub struct Batcher {
+    config: BatchConfig,
+    pending: Records,
+    /// The acks of the pushed records. They are kept while any of the records are held.
+    acks: Vec<Ack>,
+    since: Option<Instant>,
+}
+
+impl Batcher {
+    pub fn new(config: BatchConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    /// Add the records and take the batches ready to be delivered with the acks to be done after
+    /// delivering them. Without a batching window, all the records are ready.
+    pub fn push(&mut self, mut records: Records, ack: Ack) -> (Vec<Records>, Vec<Ack>) {
+        if records.is_empty() {
+            return (vec![], vec![ack]);
+        }
+
+        self.acks.push(ack);
+        let since = self.since.unwrap_or_else(Instant::now);
+        self.pending.append(&mut records);
+        let pending = self.take();
+        let mut batches = self.config.split(pending);
+
+        if self.config.window().is_some() {
+            let last = batches.last().map(|batch| {
+                let bytes = batch.iter().map(Record::size_bytes).sum();
+                self.config.is_full(batch.len(), bytes)
+            });
+            if last == Some(false) {
+                self.pending = batches.pop().unwrap_or_default();
+                self.since = Some(since);
+            }
+        }
+
+        let acks = match self.pending.is_empty() {
+            true => mem::take(&mut self.acks),
+            false => vec![],
+        };
+        (batches, acks)
+    }
+
+    /// Take all the held records in batches with all the acks.
+    pub fn flush(&mut self) -> (Vec<Records>, Vec<Ack>) {
+        let pending = self.take();
+        (self.config.split(pending), mem::take(&mut self.acks))
+    }
+
+    /// When the held records should be delivered.
+    pub fn deadline(&self) -> Option<Instant> {
+        match (self.since, self.config.window()) {
+            (Some(since), Some(window)) => Some(since + window),
+            _ => None,
+        }
+    }
+
+    fn take(&mut self) -> Records {
+        self.since = None;
+        mem::take(&mut self.pending)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records(ids: &[&str], size_bytes: i64) -> Records {
+        Records::from(
+            ids.iter()
+                .map(|id| Record::new(*id).set_size_bytes(size_bytes))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    fn ids(batches: &[Records]) -> Vec<Vec<String>> {
+        batches
+            .iter()
+            .map(|batch| {
+                batch
+                    .iter()
+                    .map(|r| r.event_id().unwrap().to_string())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn it_splits_records_by_count_and_bytes_in_order() {
+        let config = BatchConfig {
+            max_batch_records: Some(3),
+            max_batch_bytes: Some(250),
+            max_batching_window_ms: None,
+        };
+
+        let batches = config.split(records(&["0", "1", "2", "3", "4"], 100));
+        assert_eq!(
+            ids(&batches),
+            vec![vec!["0", "1"], vec!["2", "3"], vec!["4"]]
+        );
+
+        let config = BatchConfig {
+            max_batch_bytes: Some(50),
+            ..config
+        };
+        let batches = config.split(records(&["0", "1"], 100));
+        assert_eq!(ids(&batches), vec![vec!["0"], vec!["1"]]);
+    }
+
+    #[test]
+    fn it_holds_records_until_the_batch_is_full() {
+        let mut batcher = Batcher::new(BatchConfig {
+            max_batch_records: Some(3),
+            max_batch_bytes: None,
+            max_batching_window_ms: Some(1_000),
+        });
+
+        let (batches, acks) = batcher.push(records(&["0", "1"], 10), Ack::new().0);
+        assert!(batches.is_empty());
+        assert!(acks.is_empty());
+        assert!(batcher.deadline().is_some());
+
+        // The acks are kept while "3" is held.
+        let (batches, acks) = batcher.push(records(&["2", "3"], 10), Ack::new().0);
+        assert_eq!(ids(&batches), vec![vec!["0", "1", "2"]]);
+        assert!(acks.is_empty());
+        assert!(batcher.deadline().is_some());
+
+        let (batches, acks) = batcher.flush();
+        assert_eq!(ids(&batches), vec![vec!["3"]]);
+        assert_eq!(acks.len(), 2);
+        assert!(batcher.deadline().is_none());
+    }
+
+    #[test]
+    fn it_delivers_records_at_once_without_window() {
+        let mut batcher = Batcher::default();
+
+        let (batches, acks) = batcher.push(records(&["0", "1", "2"], 10), Ack::new().0);
+        assert_eq!(ids(&batches), vec![vec!["0", "1", "2"]]);
+        assert_eq!(acks.len(), 1);
+        assert!(batcher.deadline().is_none());
+    }
+}
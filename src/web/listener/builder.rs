@@ -1,9 +1,19 @@
 use super::*;
 
-#[derive(Debug, Default)]
+#[derive(Default)]
 pub struct ListenerBuilder {
-    url: Option<String>,
-    rx: Option<watch::Receiver<Records>>,
+    id: Option<String>,
+    table: Option<String>,
+    sink: Option<Arc<dyn Sink>>,
+    filter: Filter,
+    ttl: TtlDeletions,
+    projection: Projection,
+    format: PayloadFormat,
+    transform: Option<Transform>,
+    diff: bool,
+    batch: BatchConfig,
+    dead_letters: Option<Arc<dyn DeadLetterStore>>,
+    rx: Option<mpsc::Receiver<(Records, Ack)>>,
 }
 
 impl ListenerBuilder {
@@ -11,14 +21,63 @@ impl ListenerBuilder {
         Self::default()
     }
 
-    pub fn set_url<T: Into<String>>(self, url: T) -> Self {
+    pub fn set_id<T: Into<String>>(self, id: T) -> Self {
         Self {
-            url: Some(url.into()),
+            id: Some(id.into()),
             ..self
         }
     }
 
-    pub fn set_records_receiver(self, rx: watch::Receiver<Records>) -> Self {
+    pub fn set_table<T: Into<String>>(self, table: T) -> Self {
+        Self {
+            table: Some(table.into()),
+            ..self
+        }
+    }
+
+    pub fn set_sink(self, sink: Arc<dyn Sink>) -> Self {
+        Self {
+            sink: Some(sink),
+            ..self
+        }
+    }
+
+    pub fn set_filter(self, filter: Filter) -> Self {
+        Self { filter, ..self }
+    }
+
+    pub fn set_ttl_deletions(self, ttl: TtlDeletions) -> Self {
+        Self { ttl, ..self }
+    }
+
+    pub fn set_projection(self, projection: Projection) -> Self {
+        Self { projection, ..self }
+    }
+
+    pub fn set_format(self, format: PayloadFormat) -> Self {
+        Self { format, ..self }
+    }
+
+    pub fn set_transform(self, transform: Option<Transform>) -> Self {
+        Self { transform, ..self }
+    }
+
+    pub fn set_diff(self, diff: bool) -> Self {
+        Self { diff, ..self }
+    }
+
+    pub fn set_batch_config(self, batch: BatchConfig) -> Self {
+        Self { batch, ..self }
+    }
+
+    pub fn set_dead_letter_store(self, store: Option<Arc<dyn DeadLetterStore>>) -> Self {
+        Self {
+            dead_letters: store,
+            ..self
+        }
+    }
+
+    pub fn set_records_receiver(self, rx: mpsc::Receiver<(Records, Ack)>) -> Self {
         Self {
             rx: Some(rx),
             ..self
@@ -26,21 +85,36 @@ impl ListenerBuilder {
     }
 
     pub fn build(self) -> (Listener, ListenerHalf) {
-        let url = self.url.expect("\"url\" is not set to ListenerBuilder");
+        let id = self.id.expect("\"id\" is not set to ListenerBuilder");
+        let table = self.table.expect("\"table\" is not set to ListenerBuilder");
+        let sink = self.sink.expect("\"sink\" is not set to ListenerBuilder");
         let rx = self
             .rx
             .expect("\"rx_records\" is not set to ListenerBuilder");
 
         let (tx0, rx0) = oneshot::channel::<Event>();
+        let stats = Arc::new(Mutex::new(DeliveryStats::default()));
 
         let listener = Listener {
-            url,
+            id,
+            table,
+            sink,
+            filter: self.filter,
+            ttl: self.ttl,
+            projection: self.projection,
+            format: self.format,
+            transform: self.transform,
+            diff: self.diff,
+            batcher: Mutex::new(Batcher::new(self.batch)),
+            dead_letters: self.dead_letters,
             rx_event: rx0,
             rx_records: rx,
+            stats: Arc::clone(&stats),
         };
 
         let half = ListenerHalf {
             tx_event: Some(tx0),
+            stats,
         };
 
         (listener, half)
@@ -50,6 +124,16 @@ impl ListenerBuilder {
 #[derive(Debug)]
 pub struct ListenerHalf {
     tx_event: Option<oneshot::Sender<Event>>,
+    stats: Arc<Mutex<DeliveryStats>>,
+}
+
+impl ListenerHalf {
+    pub fn stats(&self) -> DeliveryStats {
+        self.stats
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
 }
 
 impl SenderHalf for ListenerHalf {
@@ -61,7 +145,7 @@ impl SenderHalf for ListenerHalf {
 impl Drop for ListenerHalf {
     // Close channel
     fn drop(&mut self) {
-        if let Some(tx) = self.tx_event().take() {
+        if let Some(tx) = self.tx_event() {
             let _ = tx.send(Event::Close);
         }
     }
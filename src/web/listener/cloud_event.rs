@@ -0,0 +1,161 @@
+use super::{Record, StreamRecord};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use ulid::Ulid;
+
+const SPEC_VERSION: &str = "1.0";
+const TYPE_PREFIX: &str = "aws.dynamodb";
+
+pub const STRUCTURED_CONTENT_TYPE: &str = "application/cloudevents+json";
+pub const DATA_CONTENT_TYPE: &str = "application/json";
+
+/// A record as a CloudEvents 1.0 event. The fields of the record out of the stream record
+/// are extension attributes.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct CloudEvent<'a> {
+    specversion: &'static str,
+    id: String,
+    source: String,
+    r#type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    time: Option<String>,
+    datacontenttype: &'static str,
+    /// `removalReason` of a `REMOVE` record.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    removalreason: Option<&'static str>,
+    /// `userIdentity.principalId`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    identityprincipalid: Option<&'a str>,
+    /// `userIdentity.type`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    identitytype: Option<&'a str>,
+    data: Option<&'a StreamRecord>,
+}
+
+impl<'a> CloudEvent<'a> {
+    pub fn new(table: &str, record: &'a Record) -> Self {
+        let id = match record.event_id() {
+            Some(id) => id.to_string(),
+            None => Ulid::new().to_string(),
+        };
+        let operation = record.event_name().map_or("unknown", |op| op.as_str());
+        let identity = record.user_identity();
+
+        Self {
+            specversion: SPEC_VERSION,
+            id,
+            source: format!("/dynamodb/tables/{table}"),
+            r#type: format!("{TYPE_PREFIX}.{}", operation.to_lowercase()),
+            time: record.approximate_creation_date_time().map(to_rfc3339),
+            datacontenttype: DATA_CONTENT_TYPE,
+            removalreason: record.removal_reason().map(|reason| reason.as_str()),
+            identityprincipalid: identity.and_then(|identity| identity.principal_id()),
+            identitytype: identity.and_then(|identity| identity.r#type()),
+            data: record.dynamodb(),
+        }
+    }
+
+    pub fn data(&self) -> Option<&'a StreamRecord> {
+        self.data
+    }
+
+    /// The `ce-` headers of the binary content mode. The data goes to the body.
+    pub fn headers(&self) -> BTreeMap<String, String> {
+        let mut headers = BTreeMap::from([
+            ("ce-specversion".to_string(), self.specversion.to_string()),
+            ("ce-id".to_string(), self.id.clone()),
+            ("ce-source".to_string(), self.source.clone()),
+            ("ce-type".to_string(), self.r#type.clone()),
+            ("content-type".to_string(), self.datacontenttype.to_string()),
+        ]);
+        let optional = [
+            ("ce-time", self.time.as_deref()),
+            ("ce-removalreason", self.removalreason),
+            ("ce-identityprincipalid", self.identityprincipalid),
+            ("ce-identitytype", self.identitytype),
+        ];
+        for (name, value) in optional {
+            if let Some(value) = value {
+                headers.insert(name.to_string(), value.to_string());
+            }
+        }
+        headers
+    }
+}
+
+fn to_rfc3339(time: &DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn record() -> Record {
+        serde_json::from_value(json!({
+            "eventId": "c4ca4238a0b923820dcc509a6f75849b",
+            "eventName": "INSERT",
+            "dynamodb": {
+                "ApproximateCreationDateTime": "2023-11-20T00:00:00Z",
+                "Keys": { "Id": { "N": "101" } },
+                "SequenceNumber": "111"
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn it_maps_record_into_cloud_event() {
+        let record = record();
+        let event = CloudEvent::new("People", &record);
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["specversion"], "1.0");
+        assert_eq!(json["id"], "c4ca4238a0b923820dcc509a6f75849b");
+        assert_eq!(json["source"], "/dynamodb/tables/People");
+        assert_eq!(json["type"], "aws.dynamodb.insert");
+        assert_eq!(json["time"], "2023-11-20T00:00:00Z");
+        assert_eq!(json["datacontenttype"], "application/json");
+        assert_eq!(json["data"]["Keys"], json!({ "Id": { "N": "101" } }));
+        assert!(json.get("removalreason").is_none());
+        assert!(json.get("identitytype").is_none());
+    }
+
+    #[test]
+    fn it_keeps_removal_reason_and_user_identity_as_extensions() {
+        let record: Record = serde_json::from_value(json!({
+            "eventId": "c4ca4238a0b923820dcc509a6f75849b",
+            "eventName": "REMOVE",
+            "dynamodb": { "Keys": { "Id": { "N": "101" } }, "SequenceNumber": "111" },
+            "userIdentity": { "principalId": "dynamodb.amazonaws.com", "type": "Service" },
+            "removalReason": "TTL_EXPIRY"
+        }))
+        .unwrap();
+        let event = CloudEvent::new("People", &record);
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["removalreason"], "TTL_EXPIRY");
+        assert_eq!(json["identityprincipalid"], "dynamodb.amazonaws.com");
+        assert_eq!(json["identitytype"], "Service");
+
+        let headers = event.headers();
+        assert_eq!(headers["ce-removalreason"], "TTL_EXPIRY");
+        assert_eq!(headers["ce-identityprincipalid"], "dynamodb.amazonaws.com");
+        assert_eq!(headers["ce-identitytype"], "Service");
+    }
+
+    #[test]
+    fn it_puts_attributes_into_headers_in_binary_mode() {
+        let record = record();
+        let headers = CloudEvent::new("People", &record).headers();
+
+        assert_eq!(headers["ce-id"], "c4ca4238a0b923820dcc509a6f75849b");
+        assert_eq!(headers["ce-type"], "aws.dynamodb.insert");
+        assert_eq!(headers["ce-source"], "/dynamodb/tables/People");
+        assert_eq!(headers["ce-time"], "2023-11-20T00:00:00Z");
+        assert_eq!(headers["content-type"], "application/json");
+    }
+}
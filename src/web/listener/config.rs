@@ -0,0 +1,64 @@
+use super::{
+    BatchConfig, Filter, OverflowPolicy, PayloadFormat, Projection, RetryConfig, Transform,
+    TtlDeletions,
+};
+
+use serde::Deserialize;
+
+const DEFAULT_BUFFER_SIZE: usize = 16;
+
+/// The options for each destination.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct ListenerConfig {
+    /// The number of records batches the listener can buffer.
+    #[serde(default = "default_buffer_size")]
+    pub buffer_size: usize,
+    /// What to do when the buffer is full.
+    #[serde(default)]
+    pub overflow_policy: OverflowPolicy,
+    /// How to retry failed deliveries.
+    #[serde(default)]
+    pub retry: RetryConfig,
+    /// Deliver only the records matching any of the filter patterns.
+    #[serde(default)]
+    pub filters: Filter,
+    /// Which deletions by TTL to deliver.
+    #[serde(default)]
+    pub ttl: TtlDeletions,
+    /// Which attributes of the images to deliver.
+    #[serde(default)]
+    pub projection: Projection,
+    /// How records are rendered in the payload.
+    #[serde(default)]
+    pub format: PayloadFormat,
+    /// Add the changes of `MODIFY` records as JSON Patch.
+    #[serde(default)]
+    pub diff: bool,
+    /// A jq expression to reshape the payload before it is sent.
+    #[serde(default)]
+    pub transform: Option<Transform>,
+    /// How records are gathered into requests.
+    #[serde(flatten)]
+    pub batch: BatchConfig,
+}
+
+impl Default for ListenerConfig {
+    fn default() -> Self {
+        Self {
+            buffer_size: DEFAULT_BUFFER_SIZE,
+            overflow_policy: OverflowPolicy::default(),
+            retry: RetryConfig::default(),
+            filters: Filter::default(),
+            ttl: TtlDeletions::default(),
+            projection: Projection::default(),
+            format: PayloadFormat::default(),
+            diff: false,
+            transform: None,
+            batch: BatchConfig::default(),
+        }
+    }
+}
+
+fn default_buffer_size() -> usize {
+    DEFAULT_BUFFER_SIZE
+}
@@ -0,0 +1,380 @@
+use super::Record;
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use tracing::error;
+
+/// The keys which filter patterns can match, like Lambda's FilterCriteria for DynamoDB.
+const EVENT_KEYS: [&str; 2] = ["eventName", "dynamodb"];
+const DYNAMODB_KEYS: [&str; 3] = ["Keys", "NewImage", "OldImage"];
+
+/// Filter patterns of a destination. A record is delivered if it matches any of the patterns.
+/// No patterns means every record is delivered.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "Vec<Value>")]
+pub struct Filter {
+    patterns: Vec<Pattern>,
+}
+
+impl Filter {
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    pub fn matches(&self, record: &Record) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+
+        match serde_json::to_value(record) {
+            Ok(value) => self.patterns.iter().any(|p| p.matches(Some(&value))),
+            Err(err) => {
+                error!("Failed to serialize record to filter: {err}");
+                false
+            }
+        }
+    }
+}
+
+impl TryFrom<Vec<Value>> for Filter {
+    type Error = String;
+
+    fn try_from(values: Vec<Value>) -> Result<Self, Self::Error> {
+        let patterns = values
+            .into_iter()
+            .map(|value| match value {
+                Value::Object(fields) => {
+                    check_keys(&fields, &EVENT_KEYS)?;
+                    if let Some(Value::Object(dynamodb)) = fields.get("dynamodb") {
+                        check_keys(dynamodb, &DYNAMODB_KEYS)?;
+                    }
+                    Pattern::try_from(Value::Object(fields))
+                }
+                _ => Err("A filter pattern should be an object".to_string()),
+            })
+            .collect::<Result<Vec<Pattern>, String>>()?;
+
+        Ok(Self { patterns })
+    }
+}
+
+fn check_keys(fields: &Map<String, Value>, allowed: &[&str]) -> Result<(), String> {
+    match fields.keys().find(|key| !allowed.contains(&key.as_str())) {
+        Some(key) => Err(format!(
+            "Unknown filter key `{key}`. Expected one of {}",
+            allowed.join(", ")
+        )),
+        None => Ok(()),
+    }
+}
+
+/// A pattern is either nested fields, all of which must match, or rules, any of which must
+/// match.
+#[derive(Debug, Clone, PartialEq)]
+enum Pattern {
+    Fields(Vec<(String, Pattern)>),
+    Rules(Vec<Rule>),
+}
+
+impl Pattern {
+    fn matches(&self, value: Option<&Value>) -> bool {
+        match self {
+            Self::Fields(fields) => fields
+                .iter()
+                .all(|(key, pattern)| pattern.matches(value.and_then(|v| v.get(key)))),
+            Self::Rules(rules) => rules.iter().any(|rule| rule.matches(value)),
+        }
+    }
+}
+
+impl TryFrom<Value> for Pattern {
+    type Error = String;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Object(fields) => fields
+                .into_iter()
+                .map(|(key, value)| Ok((key, Pattern::try_from(value)?)))
+                .collect::<Result<Vec<_>, String>>()
+                .map(Self::Fields),
+            Value::Array(rules) => rules
+                .into_iter()
+                .map(Rule::try_from)
+                .collect::<Result<Vec<_>, String>>()
+                .map(Self::Rules),
+            _ => Err(format!("Filter rules should be in an array: {value}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Operator {
+    Lt,
+    Le,
+    Eq,
+    Ge,
+    Gt,
+}
+
+impl Operator {
+    fn holds(&self, lhs: f64, rhs: f64) -> bool {
+        match self {
+            Self::Lt => lhs < rhs,
+            Self::Le => lhs <= rhs,
+            Self::Eq => lhs == rhs,
+            Self::Ge => lhs >= rhs,
+            Self::Gt => lhs > rhs,
+        }
+    }
+}
+
+impl TryFrom<&Value> for Operator {
+    type Error = String;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            Some("<") => Ok(Self::Lt),
+            Some("<=") => Ok(Self::Le),
+            Some("=") => Ok(Self::Eq),
+            Some(">=") => Ok(Self::Ge),
+            Some(">") => Ok(Self::Gt),
+            _ => Err(format!("Unknown numeric operator: {value}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Rule {
+    Equals(Value),
+    Prefix(String),
+    Numeric(Vec<(Operator, f64)>),
+    Exists(bool),
+    AnythingBut(Vec<Rule>),
+    EqualsIgnoreCase(String),
+}
+
+impl Rule {
+    fn matches(&self, value: Option<&Value>) -> bool {
+        let value = value.filter(|v| !v.is_null());
+
+        match (self, value) {
+            (Self::Exists(exists), value) => value.is_some() == *exists,
+            (_, None) => false,
+            // Lists like SS and NS match if any of the elements matches.
+            (_, Some(Value::Array(items))) => items.iter().any(|item| self.matches_value(item)),
+            (_, Some(value)) => self.matches_value(value),
+        }
+    }
+
+    fn matches_value(&self, value: &Value) -> bool {
+        match self {
+            Self::Equals(Value::Number(n)) => match (n.as_f64(), to_number(value)) {
+                (Some(n), Some(v)) => n == v,
+                _ => false,
+            },
+            Self::Equals(expected) => value == expected,
+            Self::Prefix(prefix) => value.as_str().is_some_and(|s| s.starts_with(prefix)),
+            Self::Numeric(conditions) => to_number(value)
+                .is_some_and(|v| conditions.iter().all(|(op, operand)| op.holds(v, *operand))),
+            Self::Exists(exists) => *exists,
+            Self::AnythingBut(rules) => !rules.iter().any(|rule| rule.matches_value(value)),
+            Self::EqualsIgnoreCase(expected) => value
+                .as_str()
+                .is_some_and(|s| s.to_lowercase() == expected.to_lowercase()),
+        }
+    }
+}
+
+/// Numbers of DynamoDB are serialized as strings, so they are parsed here.
+fn to_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.parse::<f64>().ok(),
+        _ => None,
+    }
+}
+
+impl TryFrom<Value> for Rule {
+    type Error = String;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let fields = match value {
+            Value::Object(fields) => fields,
+            Value::Array(_) => return Err(format!("A filter rule can't be an array: {value}")),
+            literal => return Ok(Self::Equals(literal)),
+        };
+
+        let (key, operand) = match fields.iter().next() {
+            Some(field) if fields.len() == 1 => field,
+            _ => {
+                return Err(format!(
+                    "A filter rule should have exactly one key: {}",
+                    Value::Object(fields.clone())
+                ))
+            }
+        };
+
+        match (key.as_str(), operand) {
+            ("prefix", Value::String(prefix)) => Ok(Self::Prefix(prefix.clone())),
+            ("numeric", Value::Array(items)) => parse_numeric(items),
+            ("exists", Value::Bool(exists)) => Ok(Self::Exists(*exists)),
+            ("anything-but", Value::Array(items)) => items
+                .iter()
+                .map(|item| parse_anything_but(item.clone()))
+                .collect::<Result<Vec<_>, String>>()
+                .map(Self::AnythingBut),
+            ("anything-but", item) => {
+                Ok(Self::AnythingBut(vec![parse_anything_but(item.clone())?]))
+            }
+            ("equals-ignore-case", Value::String(s)) => Ok(Self::EqualsIgnoreCase(s.clone())),
+            _ => Err(format!("Invalid filter rule: `{key}`: {operand}")),
+        }
+    }
+}
+
+fn parse_numeric(items: &[Value]) -> Result<Rule, String> {
+    if items.is_empty() || items.len() % 2 != 0 {
+        return Err("`numeric` should be pairs of an operator and a number".into());
+    }
+
+    items
+        .chunks(2)
+        .map(|pair| {
+            let op = Operator::try_from(&pair[0])?;
+            let operand = pair[1]
+                .as_f64()
+                .ok_or_else(|| format!("Numeric operand should be a number: {}", pair[1]))?;
+            Ok((op, operand))
+        })
+        .collect::<Result<Vec<_>, String>>()
+        .map(Rule::Numeric)
+}
+
+fn parse_anything_but(value: Value) -> Result<Rule, String> {
+    match Rule::try_from(value)? {
+        rule @ (Rule::Equals(_) | Rule::Prefix(_)) => Ok(rule),
+        _ => Err("`anything-but` accepts only values and `prefix`".into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn filter(patterns: Value) -> Filter {
+        serde_json::from_value(patterns).unwrap()
+    }
+
+    fn matches(filter: &Filter, record: Value) -> bool {
+        filter.patterns.iter().any(|p| p.matches(Some(&record)))
+    }
+
+    fn record(event_name: &str, new_image: Value) -> Value {
+        json!({
+            "eventName": event_name,
+            "dynamodb": {
+                "Keys": { "Id": { "S": "user-1" } },
+                "NewImage": new_image,
+                "OldImage": null
+            }
+        })
+    }
+
+    #[test]
+    fn it_matches_event_name_and_values() {
+        let f = filter(json!([{
+            "eventName": ["INSERT", "MODIFY"],
+            "dynamodb": { "Keys": { "Id": { "S": [{ "prefix": "user-" }] } } }
+        }]));
+
+        assert!(matches(&f, record("INSERT", json!({}))));
+        assert!(!matches(&f, record("REMOVE", json!({}))));
+    }
+
+    #[test]
+    fn it_matches_numeric_ranges() {
+        let f = filter(json!([{
+            "dynamodb": { "NewImage": { "Age": { "N": [{ "numeric": [">=", 20, "<", 30] }] } } }
+        }]));
+
+        assert!(matches(
+            &f,
+            record("INSERT", json!({ "Age": { "N": "20" } }))
+        ));
+        assert!(matches(
+            &f,
+            record("INSERT", json!({ "Age": { "N": "29.5" } }))
+        ));
+        assert!(!matches(
+            &f,
+            record("INSERT", json!({ "Age": { "N": "30" } }))
+        ));
+        assert!(!matches(
+            &f,
+            record("INSERT", json!({ "Age": { "S": "25" } }))
+        ));
+    }
+
+    #[test]
+    fn it_matches_exists_anything_but_and_equals_ignore_case() {
+        let f = filter(json!([{
+            "dynamodb": {
+                "NewImage": {
+                    "Email": { "S": [{ "exists": true }] },
+                    "Status": { "S": [{ "anything-but": ["deleted", "banned"] }] },
+                    "Country": { "S": [{ "equals-ignore-case": "japan" }] }
+                },
+                "OldImage": [{ "exists": false }]
+            }
+        }]));
+
+        let image = |status: &str, country: &str| {
+            json!({
+                "Email": { "S": "a@example.com" },
+                "Status": { "S": status },
+                "Country": { "S": country }
+            })
+        };
+
+        assert!(matches(&f, record("INSERT", image("active", "JAPAN"))));
+        assert!(!matches(&f, record("INSERT", image("deleted", "Japan"))));
+        assert!(!matches(&f, record("INSERT", image("active", "France"))));
+        assert!(!matches(
+            &f,
+            record(
+                "INSERT",
+                json!({ "Status": { "S": "active" }, "Country": { "S": "japan" } })
+            )
+        ));
+    }
+
+    #[test]
+    fn it_matches_any_of_the_patterns() {
+        let f = filter(json!([
+            { "eventName": ["INSERT"] },
+            { "eventName": ["REMOVE"] }
+        ]));
+
+        assert!(matches(&f, record("INSERT", json!({}))));
+        assert!(matches(&f, record("REMOVE", json!({}))));
+        assert!(!matches(&f, record("MODIFY", json!({}))));
+        assert!(Filter::default().is_empty());
+    }
+
+    #[test]
+    fn it_rejects_invalid_patterns() {
+        let invalid = [
+            json!([{ "awsRegion": ["us-east-1"] }]),
+            json!([{ "dynamodb": { "SequenceNumber": ["1"] } }]),
+            json!([{ "eventName": "INSERT" }]),
+            json!([{ "eventName": [{ "suffix": "RT" }] }]),
+            json!([{ "dynamodb": { "NewImage": { "Age": { "N": [{ "numeric": [">"] }] } } } }]),
+            json!([{ "eventName": [{ "anything-but": { "exists": true } }] }]),
+        ];
+
+        for patterns in invalid {
+            assert!(serde_json::from_value::<Filter>(patterns).is_err());
+        }
+    }
+}
@@ -0,0 +1,214 @@
+use super::{
+    cloud_event::{CloudEvent, STRUCTURED_CONTENT_TYPE},
+    DeadLetter, Records, Transform,
+};
+
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// How records are rendered in the payload for the destination.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PayloadFormat {
+    /// The same payload as a Lambda function receives from DynamoDB Streams.
+    #[default]
+    Lambda,
+    /// Images are unmarshalled into native JSON without the type descriptors.
+    PlainJson,
+    /// Each record is sent as a CloudEvents event in the body.
+    CloudEventsStructured,
+    /// Each record is sent with CloudEvents attributes in `ce-` headers and the stream record
+    /// in the body.
+    CloudEventsBinary,
+}
+
+/// A request body and its headers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Payload {
+    /// The number of records in the payload.
+    pub count: usize,
+    pub headers: BTreeMap<String, String>,
+    pub body: Value,
+}
+
+impl Payload {
+    fn new(count: usize, body: Value) -> Self {
+        Self {
+            count,
+            headers: BTreeMap::new(),
+            body,
+        }
+    }
+
+    /// Replace the body, like with a transformed one, and return the number of records which
+    /// are no longer in it. The count follows `Records` of the new body. A body without
+    /// `Records` keeps the count since it is made of the whole payload.
+    pub fn set_body(&mut self, body: Value) -> usize {
+        let count = body
+            .get("Records")
+            .and_then(Value::as_array)
+            .map_or(self.count, Vec::len);
+        let dropped = self.count.saturating_sub(count);
+        self.count = count;
+        self.body = body;
+        dropped
+    }
+
+    /// Take out the records at the positions, like the ones a sink rejected. A payload
+    /// without `Records`, like a CloudEvents event, is taken as a whole.
+    pub fn select(&self, positions: &[usize]) -> Payload {
+        let records = match self.body.get("Records").and_then(Value::as_array) {
+            Some(records) => records,
+            None => return self.clone(),
+        };
+
+        let selected: Vec<Value> = positions
+            .iter()
+            .filter_map(|i| records.get(*i).cloned())
+            .collect();
+        let mut body = self.body.clone();
+        body["Records"] = Value::Array(selected);
+
+        Payload {
+            count: body["Records"].as_array().map_or(0, Vec::len),
+            headers: self.headers.clone(),
+            body,
+        }
+    }
+}
+
+impl Payload {
+    /// Get the payload of the dead letter to send again. A payload which the transform failed on
+    /// is transformed again, and `None` means the transform dropped it this time.
+    pub fn replay(
+        letter: &DeadLetter,
+        transform: Option<&Transform>,
+    ) -> Result<Option<Payload>, String> {
+        let mut payload = Payload::from(letter);
+        if let (true, Some(transform)) = (letter.untransformed, transform) {
+            match transform.apply(payload.body.clone())? {
+                Some(body) => {
+                    payload.set_body(body);
+                }
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(payload))
+    }
+}
+
+impl From<&DeadLetter> for Payload {
+    fn from(letter: &DeadLetter) -> Self {
+        Self {
+            count: letter.records,
+            headers: letter.headers.clone(),
+            body: letter.payload.clone(),
+        }
+    }
+}
+
+impl PayloadFormat {
+    /// Render the records into payloads, one for each request.
+    pub fn render(&self, table: &str, records: &Records) -> serde_json::Result<Vec<Payload>> {
+        match self {
+            Self::Lambda => Ok(vec![Payload::new(
+                records.len(),
+                serde_json::to_value(records)?,
+            )]),
+            Self::PlainJson => Ok(vec![Payload::new(records.len(), records.to_plain_json()?)]),
+            Self::CloudEventsStructured => records
+                .iter()
+                .map(|record| {
+                    let event = CloudEvent::new(table, record);
+                    let mut payload = Payload::new(1, serde_json::to_value(event)?);
+                    payload
+                        .headers
+                        .insert("content-type".into(), STRUCTURED_CONTENT_TYPE.into());
+                    Ok(payload)
+                })
+                .collect(),
+            Self::CloudEventsBinary => records
+                .iter()
+                .map(|record| {
+                    let event = CloudEvent::new(table, record);
+                    let mut payload = Payload::new(1, serde_json::to_value(event.data())?);
+                    payload.headers = event.headers();
+                    Ok(payload)
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn it_counts_records_of_new_body() {
+        let mut payload = Payload::new(
+            3,
+            json!({ "Records": [{ "eventID": "0" }, { "eventID": "1" }, { "eventID": "2" }] }),
+        );
+
+        assert_eq!(
+            payload.set_body(json!({ "Records": [{ "eventID": "1" }] })),
+            2
+        );
+        assert_eq!(payload.count, 1);
+
+        assert_eq!(payload.set_body(json!({ "text": "INSERT: 1" })), 0);
+        assert_eq!(payload.count, 1);
+    }
+
+    #[test]
+    fn it_transforms_untransformed_dead_letter_again_on_replay() {
+        let letter = |untransformed: bool, payload: Value| DeadLetter {
+            id: "01HFVQS31XVYF5S6BFWTBTCQ6S".into(),
+            table: "People".into(),
+            destination_id: "webhook".into(),
+            error: "Failed to transform the payload".into(),
+            status: None,
+            attempts: 0,
+            failed_at: chrono::Utc::now(),
+            records: 1,
+            untransformed,
+            headers: BTreeMap::new(),
+            payload,
+        };
+        let transform: Transform =
+            serde_json::from_value(json!({ "expression": "{ id: .Records[0].eventID }" })).unwrap();
+        let records = json!({ "Records": [{ "eventID": "0" }] });
+
+        let payload = Payload::replay(&letter(true, records.clone()), Some(&transform));
+        assert_eq!(payload.unwrap().unwrap().body, json!({ "id": "0" }));
+
+        // The transform still fails, so the raw records are not sent.
+        let payload = Payload::replay(&letter(true, json!("raw")), Some(&transform));
+        assert!(payload.is_err());
+
+        // A payload failed on delivery has been transformed.
+        let payload = Payload::replay(&letter(false, records.clone()), Some(&transform));
+        assert_eq!(payload.unwrap().unwrap().body, records);
+    }
+
+    #[test]
+    fn it_selects_records_of_payload() {
+        let payload = Payload::new(
+            3,
+            json!({ "Records": [{ "eventID": "0" }, { "eventID": "1" }, { "eventID": "2" }] }),
+        );
+
+        let selected = payload.select(&[0, 2, 5]);
+        assert_eq!(selected.count, 2);
+        assert_eq!(
+            selected.body,
+            json!({ "Records": [{ "eventID": "0" }, { "eventID": "2" }] })
+        );
+
+        let event = Payload::new(1, json!({ "id": "0" }));
+        assert_eq!(event.select(&[0]), event);
+    }
+}
@@ -1,24 +1,196 @@
+mod batch;
 mod builder;
+mod cloud_event;
+mod config;
+mod filter;
+mod format;
+mod projection;
+mod retry;
+mod sink;
+mod stats;
+mod transform;
+mod ttl;
 
-use super::{Consumer, Event, ReceiverHalf, Records, SenderHalf};
+use batch::Batcher;
+
+use super::{
+    metrics, Ack, AttributeValue, Consumer, DeadLetter, DeadLetterStore, Event, OverflowPolicy,
+    ReceiverHalf, Record, Records, RemovalReason, SenderHalf, StreamRecord,
+};
 
 use axum::async_trait;
-use tokio::sync::{oneshot, watch};
-use tracing::warn;
+use chrono::Utc;
+use reqwest::StatusCode;
+use std::{
+    sync::{Arc, Mutex, MutexGuard},
+    time::Instant,
+};
+use tokio::sync::{mpsc, oneshot};
+use tracing::{error, warn};
+use ulid::Ulid;
 
+pub use batch::BatchConfig;
 pub use builder::{ListenerBuilder, ListenerHalf};
+pub use config::ListenerConfig;
+pub use filter::Filter;
+pub use format::{Payload, PayloadFormat};
+pub use projection::Projection;
+pub use retry::RetryConfig;
+pub use sink::{DeliveryError, Outcome, Sink, SinkConfig};
+pub use stats::DeliveryStats;
+pub use transform::Transform;
+pub use ttl::TtlDeletions;
 
-#[derive(Debug)]
 pub struct Listener {
-    url: String,
+    id: String,
+    table: String,
+    sink: Arc<dyn Sink>,
+    filter: Filter,
+    ttl: TtlDeletions,
+    projection: Projection,
+    format: PayloadFormat,
+    transform: Option<Transform>,
+    diff: bool,
+    batcher: Mutex<Batcher>,
+    dead_letters: Option<Arc<dyn DeadLetterStore>>,
     rx_event: oneshot::Receiver<Event>,
-    rx_records: watch::Receiver<Records>,
+    rx_records: mpsc::Receiver<(Records, Ack)>,
+    stats: Arc<Mutex<DeliveryStats>>,
 }
 
 impl Listener {
     pub fn builder() -> ListenerBuilder {
         ListenerBuilder::new()
     }
+
+    fn stats(&self) -> MutexGuard<'_, DeliveryStats> {
+        self.stats
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn batcher(&self) -> MutexGuard<'_, Batcher> {
+        self.batcher
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    async fn deliver(&self, records: Records) {
+        let payloads = match self.format.render(&self.table, &records) {
+            Ok(payloads) => payloads,
+            Err(err) => {
+                error!("Failed to serialize records: {err}");
+                return;
+            }
+        };
+
+        for payload in payloads {
+            self.send(payload).await;
+        }
+    }
+
+    async fn send(&self, mut payload: Payload) {
+        if let Some(transform) = self.transform.as_ref() {
+            match transform.apply(payload.body.clone()) {
+                Ok(Some(body)) => self.dropped(payload.set_body(body)),
+                Ok(None) => return self.dropped(payload.count),
+                Err(message) => {
+                    let err = DeliveryError {
+                        attempts: 0,
+                        status: None,
+                        message,
+                    };
+                    return self.failed(payload, err, true).await;
+                }
+            }
+        }
+
+        let labels = [self.table.as_str(), self.id.as_str()];
+        let started = Instant::now();
+        let result = self.sink.deliver(&payload).await;
+        metrics()
+            .delivery_duration
+            .with_label_values(&labels)
+            .observe(started.elapsed().as_secs_f64());
+
+        match result {
+            Ok(Outcome::Delivered(status)) => self.delivered(payload.count, status),
+            Ok(Outcome::Partial { failed, error }) => {
+                let rejected = payload.select(&failed);
+                self.delivered(payload.count.saturating_sub(rejected.count), None);
+                self.failed(rejected, error, false).await;
+            }
+            Err(err) => self.failed(payload, err, false).await,
+        }
+    }
+
+    fn delivered(&self, count: usize, status: Option<StatusCode>) {
+        metrics()
+            .records_delivered
+            .with_label_values(&[self.table.as_str(), self.id.as_str()])
+            .inc_by(count as u64);
+        self.stats().succeed(count, status);
+    }
+
+    /// Count the records which the transform dropped.
+    fn dropped(&self, count: usize) {
+        if count == 0 {
+            return;
+        }
+        metrics()
+            .records_dropped
+            .with_label_values(&[self.table.as_str(), self.id.as_str()])
+            .inc_by(count as u64);
+        self.stats().discard(count);
+    }
+
+    async fn failed(&self, payload: Payload, err: DeliveryError, untransformed: bool) {
+        metrics()
+            .records_failed
+            .with_label_values(&[self.table.as_str(), self.id.as_str()])
+            .inc_by(payload.count as u64);
+        self.stats().fail(payload.count, &err);
+        self.dead_letter(payload, err, untransformed).await;
+    }
+
+    /// Keep the records which could not be delivered. `untransformed` means the payload is kept
+    /// as it was before the transform failed on it.
+    async fn dead_letter(&self, payload: Payload, err: DeliveryError, untransformed: bool) {
+        let store = match self.dead_letters.as_ref() {
+            Some(store) => store,
+            None => {
+                warn!("Failed to send records to {}", self.sink.target());
+                warn!("{:#?}", err);
+                return;
+            }
+        };
+
+        let letter = DeadLetter {
+            id: Ulid::new().to_string(),
+            table: self.table.clone(),
+            destination_id: self.id.clone(),
+            error: err.message,
+            status: err.status.map(|s| s.as_u16()),
+            attempts: err.attempts,
+            failed_at: Utc::now(),
+            records: payload.count,
+            untransformed,
+            headers: payload.headers,
+            payload: payload.body,
+        };
+
+        warn!(
+            "Failed to send records to {}. Keep them as dead letter \"{}\": {}",
+            self.sink.target(),
+            letter.id,
+            letter.error
+        );
+
+        if let Err(err) = store.push(letter).await {
+            error!("Failed to keep dead letter: {err}");
+            error!("{:#?}", err);
+        }
+    }
 }
 
 impl ReceiverHalf for Listener {
@@ -30,26 +202,48 @@ impl ReceiverHalf for Listener {
 #[async_trait]
 impl Consumer for Listener {
     fn identifier(&self) -> &str {
-        self.url.as_str()
+        self.sink.target()
     }
 
-    fn rx_records(&mut self) -> &mut watch::Receiver<Records> {
+    fn rx_records(&mut self) -> &mut mpsc::Receiver<(Records, Ack)> {
         &mut self.rx_records
     }
 
-    async fn consume(&self, records: Records) {
+    async fn consume(&self, mut records: Records, ack: Ack) {
+        if !self.filter.is_empty() || self.ttl != TtlDeletions::Include {
+            records.retain(|record| self.ttl.matches(record) && self.filter.matches(record));
+        }
+
         if records.is_empty() {
-            return;
+            return ack.done();
+        }
+
+        if !self.projection.is_empty() {
+            records
+                .iter_mut()
+                .for_each(|record| self.projection.apply(record));
+        }
+
+        if self.diff {
+            records.iter_mut().for_each(Record::set_diff);
         }
 
-        if let Err(err) = reqwest::Client::new()
-            .post(self.url.as_str())
-            .json(&records)
-            .send()
-            .await
-        {
-            warn!("Failed to send records to {}", self.url);
-            warn!("{:#?}", err);
+        let (batches, acks) = self.batcher().push(records, ack);
+        for batch in batches {
+            self.deliver(batch).await;
+        }
+        acks.into_iter().for_each(Ack::done);
+    }
+
+    fn flush_deadline(&self) -> Option<tokio::time::Instant> {
+        self.batcher().deadline()
+    }
+
+    async fn flush(&self) {
+        let (batches, acks) = self.batcher().flush();
+        for batch in batches {
+            self.deliver(batch).await;
         }
+        acks.into_iter().for_each(Ack::done);
     }
 }
@@ -0,0 +1,92 @@
+use super::{AttributeValue, Record};
+
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Which attributes of `Keys`, `NewImage` and `OldImage` a destination receives.
+/// The attributes are kept by `include`, then dropped by `exclude` and renamed at last.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Projection {
+    /// Keep only these attributes. Every attribute is kept if not set.
+    pub include: Option<Vec<String>>,
+    /// Drop these attributes.
+    pub exclude: Vec<String>,
+    /// Rename attributes from the keys to the values.
+    pub rename: HashMap<String, String>,
+}
+
+impl Projection {
+    pub fn is_empty(&self) -> bool {
+        self.include.is_none() && self.exclude.is_empty() && self.rename.is_empty()
+    }
+
+    pub fn apply(&self, record: &mut Record) {
+        record.images_mut().for_each(|item| self.project(item));
+    }
+
+    fn project(&self, item: &mut HashMap<String, AttributeValue>) {
+        if let Some(include) = self.include.as_ref() {
+            item.retain(|name, _| include.contains(name));
+        }
+
+        item.retain(|name, _| !self.exclude.contains(name));
+
+        let renamed: Vec<(String, AttributeValue)> = self
+            .rename
+            .iter()
+            .filter_map(|(from, to)| item.remove(from).map(|value| (to.clone(), value)))
+            .collect();
+        item.extend(renamed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item() -> HashMap<String, AttributeValue> {
+        HashMap::from([
+            ("Id".to_string(), AttributeValue::S("user-1".into())),
+            ("Name".to_string(), AttributeValue::S("Joe".into())),
+            (
+                "Email".to_string(),
+                AttributeValue::S("joe@example.com".into()),
+            ),
+            ("Password".to_string(), AttributeValue::S("secret".into())),
+        ])
+    }
+
+    #[test]
+    fn it_keeps_drops_and_renames_attributes() {
+        let projection = Projection {
+            include: Some(vec!["Id".into(), "Email".into(), "Password".into()]),
+            exclude: vec!["Password".into()],
+            rename: HashMap::from([("Email".into(), "email_address".into())]),
+        };
+
+        let mut item = item();
+        projection.project(&mut item);
+
+        assert_eq!(
+            item,
+            HashMap::from([
+                ("Id".to_string(), AttributeValue::S("user-1".into())),
+                (
+                    "email_address".to_string(),
+                    AttributeValue::S("joe@example.com".into())
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn it_keeps_every_attribute_by_default() {
+        let projection = Projection::default();
+        assert!(projection.is_empty());
+
+        let mut projected = item();
+        projection.project(&mut projected);
+        assert_eq!(projected, item());
+    }
+}
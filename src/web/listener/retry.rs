@@ -0,0 +1,160 @@
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use reqwest::{header::HeaderMap, StatusCode};
+use serde::Deserialize;
+use std::time::Duration;
+
+/// The retry options for failed deliveries.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct RetryConfig {
+    /// The maximum number of attempts including the first one.
+    pub max_attempts: u32,
+    /// Give up retrying when the next attempt would start after this time from the first one.
+    pub max_elapsed_time_ms: u64,
+    /// The delay before the first retry. It doubles on every retry.
+    pub initial_interval_ms: u64,
+    /// The maximum delay between attempts.
+    pub max_interval_ms: u64,
+    /// The status codes to retry. If not set, 429 and 5xx are retried.
+    pub retryable_status_codes: Option<Vec<u16>>,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            max_elapsed_time_ms: 60_000,
+            initial_interval_ms: 500,
+            max_interval_ms: 30_000,
+            retryable_status_codes: None,
+        }
+    }
+}
+
+impl RetryConfig {
+    pub fn is_retryable(&self, status: StatusCode) -> bool {
+        match self.retryable_status_codes.as_ref() {
+            Some(codes) => codes.contains(&status.as_u16()),
+            None => status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error(),
+        }
+    }
+
+    /// Get the delay before the next attempt. `None` means the retry budget is exhausted.
+    pub fn next_delay(
+        &self,
+        attempts: u32,
+        elapsed: Duration,
+        retry_after: Option<Duration>,
+    ) -> Option<Duration> {
+        if attempts >= self.max_attempts {
+            return None;
+        }
+
+        let delay = retry_after.unwrap_or_else(|| self.backoff(attempts));
+
+        if elapsed + delay > Duration::from_millis(self.max_elapsed_time_ms) {
+            None
+        } else {
+            Some(delay)
+        }
+    }
+
+    /// Exponential backoff with jitter. The result is between half and all of the exponential
+    /// delay so that retries from many listeners don't synchronize.
+    fn backoff(&self, attempts: u32) -> Duration {
+        let exp = self
+            .initial_interval_ms
+            .saturating_mul(2u64.saturating_pow(attempts.saturating_sub(1)))
+            .min(self.max_interval_ms);
+        let jittered = rand::thread_rng().gen_range(exp / 2..=exp);
+        Duration::from_millis(jittered)
+    }
+}
+
+/// Parse `Retry-After` header, which is either delay seconds or an HTTP date.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(reqwest::header::RETRY_AFTER)?.to_str().ok()?;
+
+    if let Ok(secs) = value.trim().parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    DateTime::parse_from_rfc2822(value.trim()).ok().map(|date| {
+        (date.with_timezone(&Utc) - Utc::now())
+            .to_std()
+            .unwrap_or_default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn it_retries_429_and_5xx_by_default() {
+        let config = RetryConfig::default();
+        assert!(config.is_retryable(StatusCode::TOO_MANY_REQUESTS));
+        assert!(config.is_retryable(StatusCode::INTERNAL_SERVER_ERROR));
+        assert!(config.is_retryable(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!config.is_retryable(StatusCode::BAD_REQUEST));
+        assert!(!config.is_retryable(StatusCode::NOT_FOUND));
+
+        let config = RetryConfig {
+            retryable_status_codes: Some(vec![503]),
+            ..RetryConfig::default()
+        };
+        assert!(config.is_retryable(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!config.is_retryable(StatusCode::INTERNAL_SERVER_ERROR));
+    }
+
+    #[test]
+    fn it_backs_off_exponentially_within_the_budget() {
+        let config = RetryConfig {
+            max_attempts: 4,
+            max_elapsed_time_ms: 10_000,
+            initial_interval_ms: 100,
+            max_interval_ms: 300,
+            retryable_status_codes: None,
+        };
+        let zero = Duration::ZERO;
+
+        let delay = config.next_delay(1, zero, None).unwrap();
+        assert!(delay >= Duration::from_millis(50) && delay <= Duration::from_millis(100));
+
+        let delay = config.next_delay(2, zero, None).unwrap();
+        assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(200));
+
+        // Capped by max_interval_ms
+        let delay = config.next_delay(3, zero, None).unwrap();
+        assert!(delay >= Duration::from_millis(150) && delay <= Duration::from_millis(300));
+
+        // Exhausted max_attempts
+        assert!(config.next_delay(4, zero, None).is_none());
+
+        // Exhausted max_elapsed_time_ms
+        assert!(config
+            .next_delay(1, Duration::from_millis(9_990), None)
+            .is_none());
+    }
+
+    #[test]
+    fn it_honors_retry_after() {
+        let config = RetryConfig::default();
+        let delay = config.next_delay(1, Duration::ZERO, Some(Duration::from_secs(3)));
+        assert_eq!(delay, Some(Duration::from_secs(3)));
+
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after", HeaderValue::from_static("120"));
+        assert_eq!(retry_after(&headers), Some(Duration::from_secs(120)));
+
+        headers.insert(
+            "retry-after",
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+
+        assert_eq!(retry_after(&HeaderMap::new()), None);
+    }
+}
@@ -0,0 +1,65 @@
+use super::Secret;
+
+use reqwest::RequestBuilder;
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_API_KEY_HEADER: &str = "x-api-key";
+
+/// How requests authenticate to the endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum Auth {
+    /// `Authorization: Bearer <token>`
+    Bearer { token: Secret },
+    /// `Authorization: Basic <base64 of username:password>`
+    Basic { username: String, password: Secret },
+    /// The key in the header, `x-api-key` by default.
+    ApiKey {
+        #[serde(default = "default_api_key_header")]
+        header: String,
+        key: Secret,
+    },
+}
+
+impl Auth {
+    pub fn apply(&self, request: RequestBuilder) -> RequestBuilder {
+        match self {
+            Self::Bearer { token } => request.bearer_auth(token.expose()),
+            Self::Basic { username, password } => {
+                request.basic_auth(username, Some(password.expose()))
+            }
+            Self::ApiKey { header, key } => request.header(header, key.expose()),
+        }
+    }
+}
+
+fn default_api_key_header() -> String {
+    DEFAULT_API_KEY_HEADER.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn headers(auth: serde_json::Value) -> reqwest::header::HeaderMap {
+        let auth: Auth = serde_json::from_value(auth).unwrap();
+        let request = auth.apply(reqwest::Client::new().post("http://localhost"));
+        request.build().unwrap().headers().clone()
+    }
+
+    #[test]
+    fn it_adds_credentials_to_request() {
+        let bearer = headers(json!({ "type": "BEARER", "token": "t0k3n" }));
+        assert_eq!(bearer["authorization"], "Bearer t0k3n");
+
+        let basic = headers(json!({ "type": "BASIC", "username": "joe", "password": "pass" }));
+        assert_eq!(basic["authorization"], "Basic am9lOnBhc3M=");
+
+        let api_key = headers(json!({ "type": "API_KEY", "key": "k3y" }));
+        assert_eq!(api_key["x-api-key"], "k3y");
+
+        let api_key = headers(json!({ "type": "API_KEY", "header": "x-token", "key": "k3y" }));
+        assert_eq!(api_key["x-token"], "k3y");
+    }
+}
@@ -0,0 +1,119 @@
+use super::{
+    FileConfig, FileSink, HttpConfig, RetryConfig, Sink, StdoutConfig, StdoutSink, Webhook,
+};
+
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Where a destination delivers records.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SinkConfig {
+    /// POST payloads to the url.
+    Http(HttpConfig),
+    /// Append records to local files as NDJSON.
+    File(FileConfig),
+    /// Write payloads to stdout.
+    Stdout(StdoutConfig),
+}
+
+impl SinkConfig {
+    /// Get the destination of an entry, which has either `url` of an HTTP endpoint or `sink`.
+    pub fn resolve(url: Option<String>, sink: Option<SinkConfig>) -> Result<Self, String> {
+        match (url, sink) {
+            (Some(url), None) => Ok(Self::Http(HttpConfig::new(url))),
+            (None, Some(sink)) => Ok(sink),
+            (Some(_), Some(_)) => Err("`url` and `sink` cannot be set together".into()),
+            (None, None) => Err("missing field `url` or `sink`".into()),
+        }
+    }
+
+    pub fn build(&self, table: &str, retry: RetryConfig) -> Arc<dyn Sink> {
+        match self {
+            Self::Http(config) => Arc::new(Webhook::new(config.clone(), retry)),
+            Self::File(config) => Arc::new(FileSink::new(table, config.clone())),
+            Self::Stdout(config) => Arc::new(StdoutSink::new(table, config.clone())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn it_resolves_url_or_typed_sink() {
+        let url = "http://localhost:9000".to_string();
+        let http = SinkConfig::Http(HttpConfig::new(url.clone()));
+
+        assert_eq!(
+            SinkConfig::resolve(Some(url.clone()), None),
+            Ok(http.clone())
+        );
+        assert_eq!(
+            SinkConfig::resolve(None, Some(http.clone())),
+            Ok(http.clone())
+        );
+        assert!(SinkConfig::resolve(Some(url), Some(http.clone())).is_err());
+        assert!(SinkConfig::resolve(None, None).is_err());
+
+        let sink: SinkConfig =
+            serde_json::from_value(json!({ "type": "HTTP", "url": "http://localhost:9000" }))
+                .unwrap();
+        assert_eq!(sink, http);
+        assert_eq!(
+            serde_json::to_value(&sink).unwrap(),
+            json!({ "type": "HTTP", "url": "http://localhost:9000" })
+        );
+
+        let sink: SinkConfig = serde_json::from_value(
+            json!({ "type": "FILE", "path": "/tmp/{table}.ndjson", "gzip": true }),
+        )
+        .unwrap();
+        assert_eq!(
+            sink,
+            SinkConfig::File(FileConfig {
+                path: "/tmp/{table}.ndjson".into(),
+                max_bytes: None,
+                rotation_interval_secs: None,
+                gzip: true,
+            })
+        );
+
+        let sink: SinkConfig = serde_json::from_value(json!({ "type": "STDOUT" })).unwrap();
+        assert_eq!(sink, SinkConfig::Stdout(StdoutConfig::default()));
+    }
+
+    #[test]
+    fn it_redacts_secrets_of_http_sink() {
+        let sink: SinkConfig = serde_json::from_value(json!({
+            "type": "HTTP",
+            "url": "http://localhost:9000",
+            "headers": {
+                "Authorization": "Bearer b34r3r",
+                "x-tenant": "acme",
+                "x-token": { "secret": "t0k3n" }
+            },
+            "auth": { "type": "BASIC", "username": "joe", "password": "s3cr3t" },
+            "signing": { "secrets": ["new-secret", "old-secret"] }
+        }))
+        .unwrap();
+        assert!(matches!(&sink, SinkConfig::Http(config) if config.signing.is_some()));
+
+        let redacted = json!({
+            "type": "HTTP",
+            "url": "http://localhost:9000",
+            "headers": {
+                "Authorization": "[REDACTED]",
+                "x-tenant": "acme",
+                "x-token": { "secret": "[REDACTED]" }
+            },
+            "auth": { "type": "BASIC", "username": "joe", "password": "[REDACTED]" },
+            "signing": { "secrets": ["[REDACTED]", "[REDACTED]"] }
+        });
+        assert_eq!(serde_json::to_value(&sink).unwrap(), redacted);
+        assert!(!format!("{sink:?}").contains("s3cr3t"));
+        assert!(!format!("{sink:?}").contains("t0k3n"));
+    }
+}
@@ -0,0 +1,281 @@
+use super::{DeliveryError, Outcome, Payload, Sink};
+
+use axum::async_trait;
+use chrono::{DateTime, Utc};
+use flate2::{write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    io,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+use tokio::{fs, io::AsyncWriteExt, sync::Mutex, task};
+use ulid::Ulid;
+
+/// The options of a sink appending records to local files as NDJSON.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FileConfig {
+    /// The path of the file to append to. `{table}`, `{date}` and `{hour}` are replaced with
+    /// the table name and the current date and hour in UTC.
+    pub path: String,
+    /// Rotate the file before it grows over this size.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_bytes: Option<u64>,
+    /// Rotate the file after this time since it was opened.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rotation_interval_secs: Option<u64>,
+    /// Compress rotated files with gzip.
+    #[serde(default)]
+    pub gzip: bool,
+}
+
+/// A sink appending each record in a line of JSON. A payload without `Records`, like a
+/// CloudEvents event, is written in a line.
+#[derive(Debug)]
+pub struct FileSink {
+    table: String,
+    config: FileConfig,
+    segment: Mutex<Option<Segment>>,
+}
+
+/// The file being appended to.
+#[derive(Debug)]
+struct Segment {
+    path: PathBuf,
+    file: fs::File,
+    bytes: u64,
+    opened_at: Instant,
+}
+
+impl FileSink {
+    pub fn new<T: Into<String>>(table: T, config: FileConfig) -> Self {
+        Self {
+            table: table.into(),
+            config,
+            segment: Mutex::new(None),
+        }
+    }
+
+    fn path(&self, now: DateTime<Utc>) -> PathBuf {
+        let path = self
+            .config
+            .path
+            .replace("{table}", &self.table)
+            .replace("{date}", &now.format("%Y-%m-%d").to_string())
+            .replace("{hour}", &now.format("%H").to_string());
+        PathBuf::from(path)
+    }
+
+    fn is_due(&self, segment: &Segment, bytes: u64) -> bool {
+        let full = self
+            .config
+            .max_bytes
+            .is_some_and(|max| segment.bytes > 0 && segment.bytes + bytes > max);
+        let expired = self
+            .config
+            .rotation_interval_secs
+            .is_some_and(|secs| segment.opened_at.elapsed() >= Duration::from_secs(secs));
+        full || expired
+    }
+
+    async fn write(&self, content: &[u8]) -> io::Result<()> {
+        let mut segment = self.segment.lock().await;
+        let path = self.path(Utc::now());
+        let bytes = content.len() as u64;
+
+        if let Some(current) = segment.take() {
+            if current.path != path || self.is_due(&current, bytes) {
+                rotate(current.path, self.config.gzip).await?;
+            } else {
+                *segment = Some(current);
+            }
+        }
+
+        let current = match segment.as_mut() {
+            Some(current) => current,
+            None => segment.insert(Segment::open(path).await?),
+        };
+
+        current.file.write_all(content).await?;
+        current.file.flush().await?;
+        current.bytes += bytes;
+
+        Ok(())
+    }
+}
+
+impl Segment {
+    async fn open(path: PathBuf) -> io::Result<Self> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).await?;
+        }
+
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await?;
+        let bytes = file.metadata().await?.len();
+
+        Ok(Self {
+            path,
+            file,
+            bytes,
+            opened_at: Instant::now(),
+        })
+    }
+}
+
+/// Rename the file with a unique suffix so that the next write starts a new one.
+async fn rotate(path: PathBuf, gzip: bool) -> io::Result<()> {
+    let rotated = suffixed(&path, &Ulid::new().to_string());
+    fs::rename(&path, &rotated).await?;
+
+    if gzip {
+        task::spawn_blocking(move || compress(&rotated))
+            .await
+            .map_err(io::Error::other)??;
+    }
+    Ok(())
+}
+
+fn compress(path: &Path) -> io::Result<()> {
+    let mut input = std::fs::File::open(path)?;
+    let output = std::fs::File::create(suffixed(path, "gz"))?;
+    let mut encoder = GzEncoder::new(output, Compression::default());
+    io::copy(&mut input, &mut encoder)?;
+    encoder.finish()?;
+    std::fs::remove_file(path)
+}
+
+fn suffixed(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".");
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+fn to_lines(body: &Value) -> serde_json::Result<Vec<u8>> {
+    let values = match body.get("Records").and_then(Value::as_array) {
+        Some(records) => records.iter().collect(),
+        None => vec![body],
+    };
+
+    let mut content = vec![];
+    for value in values {
+        serde_json::to_writer(&mut content, value)?;
+        content.push(b'\n');
+    }
+    Ok(content)
+}
+
+#[async_trait]
+impl Sink for FileSink {
+    fn target(&self) -> &str {
+        self.config.path.as_str()
+    }
+
+    async fn deliver(&self, payload: &Payload) -> Result<Outcome, DeliveryError> {
+        let error = |message: String| DeliveryError {
+            attempts: 1,
+            status: None,
+            message,
+        };
+
+        let content = to_lines(&payload.body).map_err(|err| error(format!("{err}")))?;
+        self.write(&content)
+            .await
+            .map_err(|err| error(format!("Failed to write to {}: {err}", self.config.path)))?;
+
+        Ok(Outcome::Delivered(None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use serde_json::json;
+    use std::{collections::BTreeMap, io::Read};
+
+    fn payload(ids: &[&str]) -> Payload {
+        let records: Vec<Value> = ids.iter().map(|id| json!({ "eventID": id })).collect();
+        Payload {
+            count: ids.len(),
+            headers: BTreeMap::new(),
+            body: json!({ "Records": records }),
+        }
+    }
+
+    fn files(dir: &Path) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[tokio::test]
+    async fn it_appends_records_as_ndjson_to_templated_path() {
+        let dir = std::env::temp_dir().join(Ulid::new().to_string());
+        let sink = FileSink::new(
+            "People",
+            FileConfig {
+                path: format!("{}/{{table}}/{{date}}.ndjson", dir.display()),
+                max_bytes: None,
+                rotation_interval_secs: None,
+                gzip: false,
+            },
+        );
+
+        sink.deliver(&payload(&["0", "1"])).await.unwrap();
+        sink.deliver(&payload(&["2"])).await.unwrap();
+
+        let path = dir
+            .join("People")
+            .join(format!("{}.ndjson", Utc::now().format("%Y-%m-%d")));
+        let content = std::fs::read_to_string(path).unwrap();
+        assert_eq!(
+            content,
+            "{\"eventID\":\"0\"}\n{\"eventID\":\"1\"}\n{\"eventID\":\"2\"}\n"
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn it_rotates_files_by_size_and_compresses_them() {
+        let dir = std::env::temp_dir().join(Ulid::new().to_string());
+        let sink = FileSink::new(
+            "People",
+            FileConfig {
+                path: format!("{}/records.ndjson", dir.display()),
+                max_bytes: Some(40),
+                rotation_interval_secs: None,
+                gzip: true,
+            },
+        );
+
+        // Each record takes 16 bytes in a line.
+        sink.deliver(&payload(&["0", "1"])).await.unwrap();
+        sink.deliver(&payload(&["2"])).await.unwrap();
+
+        let files = files(&dir);
+        assert_eq!(files.len(), 2);
+        assert!(files[0].ends_with("records.ndjson"));
+        assert!(files[1].to_string_lossy().ends_with(".gz"));
+
+        let mut rotated = String::new();
+        GzDecoder::new(std::fs::File::open(&files[1]).unwrap())
+            .read_to_string(&mut rotated)
+            .unwrap();
+        assert_eq!(rotated, "{\"eventID\":\"0\"}\n{\"eventID\":\"1\"}\n");
+
+        let active = std::fs::read_to_string(&files[0]).unwrap();
+        assert_eq!(active, "{\"eventID\":\"2\"}\n");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
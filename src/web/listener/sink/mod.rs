@@ -0,0 +1,53 @@
+mod auth;
+mod config;
+mod file;
+mod secret;
+mod signing;
+mod stdout;
+mod webhook;
+
+use super::{retry, AttributeValue, Payload, RetryConfig};
+
+use axum::async_trait;
+use reqwest::StatusCode;
+use std::fmt;
+use thiserror::Error;
+
+pub use auth::Auth;
+pub use config::SinkConfig;
+pub use file::{FileConfig, FileSink};
+pub use secret::{Secret, REDACTED};
+pub use signing::SigningConfig;
+pub use stdout::{StdoutConfig, StdoutSink};
+pub use webhook::{HttpConfig, Webhook};
+
+#[derive(Debug, Error)]
+#[error("Failed to deliver records after {attempts} attempt(s): {message}")]
+pub struct DeliveryError {
+    pub attempts: u32,
+    pub status: Option<StatusCode>,
+    pub message: String,
+}
+
+/// The result of a delivery the sink has accepted.
+#[derive(Debug)]
+pub enum Outcome {
+    /// All the records of the payload were delivered. HTTP sinks tell the response status.
+    Delivered(Option<StatusCode>),
+    /// Only some of the records were delivered. `failed` are the positions of the rejected
+    /// records in `Records` of the payload.
+    Partial {
+        failed: Vec<usize>,
+        error: DeliveryError,
+    },
+}
+
+/// A system to which a listener delivers the payloads of its destination.
+#[async_trait]
+pub trait Sink: fmt::Debug + Send + Sync {
+    /// Where the payloads go, like the url. It is used in logs.
+    fn target(&self) -> &str;
+
+    /// Deliver the payload of a batch. `Err` means none of the records were delivered.
+    async fn deliver(&self, payload: &Payload) -> Result<Outcome, DeliveryError>;
+}
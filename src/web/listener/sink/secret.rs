@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize, Serializer};
+use std::fmt;
+
+pub const REDACTED: &str = "[REDACTED]";
+
+/// A configured value which must not be shown, like a token. It is redacted in the status and
+/// in logs.
+#[derive(Clone, Deserialize, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn expose(&self) -> &str {
+        self.0.as_str()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl From<&str> for Secret {
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
+impl Serialize for Secret {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(REDACTED)
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn it_redacts_secret_in_serialization_and_debug() {
+        let secret: Secret = serde_json::from_value(json!("token")).unwrap();
+
+        assert_eq!(secret.expose(), "token");
+        assert_eq!(serde_json::to_value(&secret).unwrap(), json!("[REDACTED]"));
+        assert_eq!(format!("{secret:?}"), "[REDACTED]");
+    }
+}
@@ -0,0 +1,123 @@
+use super::Secret;
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+pub const DELIVERY_ID_HEADER: &str = "x-dynamo-stream-delivery-id";
+pub const TIMESTAMP_HEADER: &str = "x-dynamo-stream-timestamp";
+pub const SIGNATURE_HEADER: &str = "x-dynamo-stream-signature";
+
+const MAX_SECRETS: usize = 2;
+
+/// The secrets to sign requests with HMAC-SHA256 so that receivers can verify where they
+/// came from.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(try_from = "RawSigningConfig")]
+pub struct SigningConfig {
+    secrets: Vec<Secret>,
+}
+
+/// One secret, or two while rotating them.
+#[derive(Deserialize)]
+struct RawSigningConfig {
+    secrets: Vec<Secret>,
+}
+
+impl TryFrom<RawSigningConfig> for SigningConfig {
+    type Error = String;
+
+    fn try_from(raw: RawSigningConfig) -> Result<Self, Self::Error> {
+        if raw.secrets.is_empty() || raw.secrets.len() > MAX_SECRETS {
+            return Err(format!(
+                "`signing.secrets` must have 1 to {MAX_SECRETS} secrets"
+            ));
+        }
+        if raw.secrets.iter().any(Secret::is_empty) {
+            return Err("`signing.secrets` must not have an empty secret".into());
+        }
+        Ok(Self {
+            secrets: raw.secrets,
+        })
+    }
+}
+
+impl SigningConfig {
+    /// Get the headers signing the body delivered at the timestamp in unix seconds. The
+    /// signature has a `v1=` entry for each secret.
+    pub fn headers(
+        &self,
+        delivery_id: &str,
+        timestamp: i64,
+        body: &[u8],
+    ) -> [(&'static str, String); 3] {
+        let signature = self
+            .secrets
+            .iter()
+            .map(|secret| {
+                let signature = sign(secret.expose(), delivery_id, timestamp, body);
+                format!("v1={signature}")
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+
+        [
+            (DELIVERY_ID_HEADER, delivery_id.to_string()),
+            (TIMESTAMP_HEADER, timestamp.to_string()),
+            (SIGNATURE_HEADER, signature),
+        ]
+    }
+}
+
+/// HMAC-SHA256 of `{timestamp}.{delivery_id}.{body}` in hex.
+fn sign(secret: &str, delivery_id: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC can take a key of any size");
+    mac.update(format!("{timestamp}.{delivery_id}.").as_bytes());
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn it_signs_body_with_each_active_secret() {
+        let config: SigningConfig =
+            serde_json::from_value(json!({ "secrets": ["secret-1", "secret-0"] })).unwrap();
+
+        let headers = config.headers(
+            "01HFVQS31XVYF5S6BFWTBTCQ6S",
+            1_700_438_400,
+            br#"{"Records":[]}"#,
+        );
+        assert_eq!(
+            headers,
+            [
+                (DELIVERY_ID_HEADER, "01HFVQS31XVYF5S6BFWTBTCQ6S".to_string()),
+                (TIMESTAMP_HEADER, "1700438400".to_string()),
+                (
+                    SIGNATURE_HEADER,
+                    "v1=a073c67c795c594c8442ef4dbe9bb463540f679facacc3231bea579456ae09d8,\
+                     v1=8e42df367388906f485cd4653c041372ea6cad91e5752cbd4f42859dfeb7f58d"
+                        .to_string()
+                ),
+            ]
+        );
+        assert!(!format!("{config:?}").contains("secret-1"));
+    }
+
+    #[test]
+    fn it_accepts_up_to_two_secrets() {
+        let config = |secrets: serde_json::Value| {
+            serde_json::from_value::<SigningConfig>(json!({ "secrets": secrets }))
+        };
+
+        assert!(config(json!(["a"])).is_ok());
+        assert!(config(json!([])).is_err());
+        assert!(config(json!(["a", "b", "c"])).is_err());
+        assert!(config(json!([""])).is_err());
+    }
+}
@@ -0,0 +1,217 @@
+use super::{AttributeValue, DeliveryError, Outcome, Payload, Sink};
+
+use axum::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+};
+
+/// The options of a sink writing payloads to stdout.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StdoutConfig {
+    #[serde(default)]
+    pub format: StdoutFormat,
+    /// Whether a line is written for each payload or each record in `JSON_LINES` format.
+    #[serde(default)]
+    pub scope: StdoutScope,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum StdoutFormat {
+    /// JSON in a line.
+    #[default]
+    JsonLines,
+    /// The table, the operation, the keys and the sequence number of each record in a line.
+    Summary,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum StdoutScope {
+    /// The whole payload.
+    #[default]
+    Payload,
+    /// Each element of `Records` in the payload.
+    Record,
+}
+
+/// A sink writing payloads to stdout so that log pipelines can pick them up.
+#[derive(Debug)]
+pub struct StdoutSink {
+    table: String,
+    config: StdoutConfig,
+}
+
+impl StdoutSink {
+    pub fn new<T: Into<String>>(table: T, config: StdoutConfig) -> Self {
+        Self {
+            table: table.into(),
+            config,
+        }
+    }
+
+    fn render(&self, payload: &Payload) -> serde_json::Result<Vec<u8>> {
+        let mut content = vec![];
+
+        match (self.config.format, self.config.scope) {
+            (StdoutFormat::JsonLines, StdoutScope::Payload) => {
+                serde_json::to_writer(&mut content, &payload.body)?;
+                content.push(b'\n');
+            }
+            (StdoutFormat::JsonLines, StdoutScope::Record) => {
+                for record in records(&payload.body) {
+                    serde_json::to_writer(&mut content, record)?;
+                    content.push(b'\n');
+                }
+            }
+            (StdoutFormat::Summary, _) => {
+                for record in records(&payload.body) {
+                    content.extend(self.summarize(record, payload).into_bytes());
+                    content.push(b'\n');
+                }
+            }
+        }
+
+        Ok(content)
+    }
+
+    /// Summarize a record in the Lambda or plain JSON format, or a CloudEvents event.
+    fn summarize(&self, record: &Value, payload: &Payload) -> String {
+        let operation = record
+            .get("eventName")
+            .or_else(|| record.get("type"))
+            .and_then(Value::as_str)
+            .or_else(|| payload.headers.get("ce-type").map(String::as_str))
+            .unwrap_or("-");
+        let stream_record = record
+            .get("dynamodb")
+            .or_else(|| record.get("data"))
+            .unwrap_or(record);
+        let keys = stream_record.get("Keys").map_or("-".to_string(), |keys| {
+            let keys = serde_json::from_value::<HashMap<String, AttributeValue>>(keys.clone())
+                .map(|item| {
+                    item.iter()
+                        .map(|(key, value)| (key.clone(), value.unmarshall()))
+                        .collect()
+                })
+                .unwrap_or_else(|_| keys.clone());
+            keys.to_string()
+        });
+        let sequence_number = stream_record
+            .get("SequenceNumber")
+            .and_then(Value::as_str)
+            .unwrap_or("-");
+
+        format!(
+            "{} {operation} keys={keys} seq={sequence_number}",
+            self.table
+        )
+    }
+}
+
+/// The records in the payload. A payload without `Records` is taken as a record.
+fn records(body: &Value) -> Vec<&Value> {
+    match body.get("Records").and_then(Value::as_array) {
+        Some(records) => records.iter().collect(),
+        None => vec![body],
+    }
+}
+
+#[async_trait]
+impl Sink for StdoutSink {
+    fn target(&self) -> &str {
+        "stdout"
+    }
+
+    async fn deliver(&self, payload: &Payload) -> Result<Outcome, DeliveryError> {
+        let error = |message: String| DeliveryError {
+            attempts: 1,
+            status: None,
+            message,
+        };
+
+        let content = self
+            .render(payload)
+            .map_err(|err| error(format!("{err}")))?;
+        // Write all the lines at once not to interleave with other destinations.
+        io::stdout()
+            .lock()
+            .write_all(&content)
+            .map_err(|err| error(format!("Failed to write to stdout: {err}")))?;
+
+        Ok(Outcome::Delivered(None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::BTreeMap;
+
+    fn payload() -> Payload {
+        Payload {
+            count: 2,
+            headers: BTreeMap::new(),
+            body: json!({
+                "Records": [
+                    {
+                        "eventName": "INSERT",
+                        "dynamodb": { "Keys": { "Id": { "N": "101" } }, "SequenceNumber": "111" }
+                    },
+                    {
+                        "eventName": "REMOVE",
+                        "dynamodb": { "Keys": { "Id": 102 }, "SequenceNumber": "222" }
+                    }
+                ]
+            }),
+        }
+    }
+
+    fn render(format: StdoutFormat, scope: StdoutScope, payload: &Payload) -> String {
+        let sink = StdoutSink::new("People", StdoutConfig { format, scope });
+        String::from_utf8(sink.render(payload).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn it_renders_json_lines_of_payload_or_records() {
+        let payload = payload();
+
+        let lines = render(StdoutFormat::JsonLines, StdoutScope::Payload, &payload);
+        assert_eq!(lines.lines().count(), 1);
+        assert_eq!(
+            serde_json::from_str::<Value>(lines.trim_end()).unwrap(),
+            payload.body
+        );
+
+        let lines = render(StdoutFormat::JsonLines, StdoutScope::Record, &payload);
+        let records: Vec<Value> = lines
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(&Value::Array(records), &payload.body["Records"]);
+    }
+
+    #[test]
+    fn it_summarizes_each_record() {
+        let lines = render(StdoutFormat::Summary, StdoutScope::Payload, &payload());
+        assert_eq!(
+            lines,
+            "People INSERT keys={\"Id\":101} seq=111\nPeople REMOVE keys={\"Id\":102} seq=222\n"
+        );
+
+        let event = Payload {
+            count: 1,
+            headers: BTreeMap::from([("ce-type".to_string(), "aws.dynamodb.modify".to_string())]),
+            body: json!({ "Keys": { "Id": { "S": "a" } }, "SequenceNumber": "333" }),
+        };
+        let lines = render(StdoutFormat::Summary, StdoutScope::Record, &event);
+        assert_eq!(
+            lines,
+            "People aws.dynamodb.modify keys={\"Id\":\"a\"} seq=333\n"
+        );
+    }
+}
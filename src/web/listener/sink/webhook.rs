@@ -0,0 +1,229 @@
+use super::{
+    retry, Auth, DeliveryError, Outcome, Payload, RetryConfig, Secret, SigningConfig, Sink,
+    REDACTED,
+};
+
+use axum::async_trait;
+use chrono::Utc;
+use reqwest::{header::CONTENT_TYPE, StatusCode};
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::BTreeMap;
+use tokio::time::{sleep, Instant};
+use tracing::warn;
+use ulid::Ulid;
+
+/// Headers carrying credentials. Their values are redacted in the status even if they are not
+/// marked as secret.
+const SENSITIVE_HEADERS: [&str; 4] = [
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "x-api-key",
+];
+
+/// The options of a sink sending payloads to an http endpoint. Credentials, signing secrets
+/// and header values marked as secret are redacted in the status.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HttpConfig {
+    pub url: String,
+    /// Static headers of every request. The headers of the payload take precedence.
+    #[serde(
+        default,
+        skip_serializing_if = "BTreeMap::is_empty",
+        serialize_with = "serialize_headers"
+    )]
+    pub headers: BTreeMap<String, HeaderValue>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<Auth>,
+    /// Sign requests with the secrets.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signing: Option<SigningConfig>,
+}
+
+/// The value of a static header. A value which must not be shown, like a token, is marked as
+/// `{ secret: <value> }`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum HeaderValue {
+    Plain(String),
+    Secret { secret: Secret },
+}
+
+impl HeaderValue {
+    pub fn expose(&self) -> &str {
+        match self {
+            Self::Plain(value) => value.as_str(),
+            Self::Secret { secret } => secret.expose(),
+        }
+    }
+}
+
+/// Serialize the headers with the values of sensitive headers redacted.
+fn serialize_headers<S: Serializer>(
+    headers: &BTreeMap<String, HeaderValue>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(headers.iter().map(|(name, value)| {
+        let sensitive = SENSITIVE_HEADERS
+            .iter()
+            .any(|header| header.eq_ignore_ascii_case(name));
+        match value {
+            HeaderValue::Plain(_) if sensitive => (name, HeaderValue::Plain(REDACTED.into())),
+            value => (name, value.clone()),
+        }
+    }))
+}
+
+impl HttpConfig {
+    pub fn new<T: Into<String>>(url: T) -> Self {
+        Self {
+            url: url.into(),
+            headers: BTreeMap::new(),
+            auth: None,
+            signing: None,
+        }
+    }
+}
+
+/// An http endpoint receiving records via POST requests.
+#[derive(Debug, Clone)]
+pub struct Webhook {
+    config: HttpConfig,
+    retry: RetryConfig,
+    client: reqwest::Client,
+}
+
+impl Webhook {
+    pub fn new(config: HttpConfig, retry: RetryConfig) -> Self {
+        Self {
+            config,
+            retry,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        self.config.url.as_str()
+    }
+
+    /// POST the body with the headers to the url. Failed requests are retried following the
+    /// retry config. With signing secrets, every attempt is signed at the time and shares the
+    /// delivery id.
+    pub async fn post<T: Serialize + ?Sized>(
+        &self,
+        body: &T,
+        headers: &BTreeMap<String, String>,
+    ) -> Result<StatusCode, DeliveryError> {
+        let started = Instant::now();
+        let mut attempts: u32 = 0;
+
+        let body = serde_json::to_vec(body).map_err(|err| DeliveryError {
+            attempts,
+            status: None,
+            message: format!("{err}"),
+        })?;
+        let delivery_id = Ulid::new().to_string();
+
+        let has = |name: &str| headers.keys().any(|key| key.eq_ignore_ascii_case(name));
+        let static_headers: Vec<(&String, &str)> = self
+            .config
+            .headers
+            .iter()
+            .filter(|(key, _)| !has(key))
+            .map(|(key, value)| (key, value.expose()))
+            .collect();
+        let has_content_type = has(CONTENT_TYPE.as_str())
+            || static_headers
+                .iter()
+                .any(|(key, _)| key.eq_ignore_ascii_case(CONTENT_TYPE.as_str()));
+
+        loop {
+            attempts += 1;
+
+            let mut request = headers
+                .iter()
+                .map(|(key, value)| (key, value.as_str()))
+                .chain(static_headers.iter().copied())
+                .fold(self.client.post(self.url()), |request, (key, value)| {
+                    request.header(key, value)
+                });
+            if !has_content_type {
+                request = request.header(CONTENT_TYPE, "application/json");
+            }
+            if let Some(auth) = self.config.auth.as_ref() {
+                request = auth.apply(request);
+            }
+            if let Some(signing) = self.config.signing.as_ref() {
+                let timestamp = Utc::now().timestamp();
+                for (key, value) in signing.headers(&delivery_id, timestamp, &body) {
+                    request = request.header(key, value);
+                }
+            }
+
+            let (error, retry_after) = match request.body(body.clone()).send().await {
+                Ok(res) if res.status().is_success() => {
+                    return Ok(res.status());
+                }
+                Ok(res) => {
+                    let status = res.status();
+                    let error = DeliveryError {
+                        attempts,
+                        status: Some(status),
+                        message: format!("The response status is {status}"),
+                    };
+
+                    if !self.retry.is_retryable(status) {
+                        return Err(error);
+                    }
+
+                    (error, retry::retry_after(res.headers()))
+                }
+                Err(err) => {
+                    let error = DeliveryError {
+                        attempts,
+                        status: None,
+                        message: format!("{err}"),
+                    };
+
+                    // The request itself is invalid so that it never succeeds.
+                    if err.is_builder() {
+                        return Err(error);
+                    }
+
+                    (error, None)
+                }
+            };
+
+            match self
+                .retry
+                .next_delay(attempts, started.elapsed(), retry_after)
+            {
+                Some(delay) => {
+                    warn!(
+                        "Failed to send records to {}. Retry in {}ms: {}",
+                        self.url(),
+                        delay.as_millis(),
+                        error.message
+                    );
+                    sleep(delay).await;
+                }
+                None => {
+                    return Err(error);
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Sink for Webhook {
+    fn target(&self) -> &str {
+        self.url()
+    }
+
+    async fn deliver(&self, payload: &Payload) -> Result<Outcome, DeliveryError> {
+        self.post(&payload.body, &payload.headers)
+            .await
+            .map(|status| Outcome::Delivered(Some(status)))
+    }
+}
@@ -0,0 +1,67 @@
+use super::DeliveryError;
+
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use serde::Serialize;
+
+/// The delivery statistics of a listener.
+#[derive(Debug, Default, Clone, PartialEq, Serialize)]
+pub struct DeliveryStats {
+    /// The number of records delivered.
+    pub delivered: u64,
+    /// The number of records which could not be delivered.
+    pub failed: u64,
+    /// The number of records which the transform dropped.
+    pub dropped: u64,
+    pub last_status_code: Option<u16>,
+    pub last_delivered_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+}
+
+impl DeliveryStats {
+    pub fn succeed(&mut self, records: usize, status: Option<StatusCode>) {
+        self.delivered += records as u64;
+        self.last_status_code = status.map(|s| s.as_u16());
+        self.last_delivered_at = Some(Utc::now());
+    }
+
+    pub fn discard(&mut self, records: usize) {
+        self.dropped += records as u64;
+    }
+
+    pub fn fail(&mut self, records: usize, err: &DeliveryError) {
+        self.failed += records as u64;
+        self.last_status_code = err.status.map(|s| s.as_u16());
+        self.last_error = Some(err.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_counts_delivered_and_failed_records() {
+        let mut stats = DeliveryStats::default();
+
+        stats.succeed(3, Some(StatusCode::OK));
+        assert_eq!(stats.delivered, 3);
+        assert_eq!(stats.last_status_code, Some(200));
+        assert!(stats.last_delivered_at.is_some());
+
+        let err = DeliveryError {
+            attempts: 5,
+            status: Some(StatusCode::SERVICE_UNAVAILABLE),
+            message: "The response status is 503 Service Unavailable".into(),
+        };
+        stats.fail(2, &err);
+        assert_eq!(stats.delivered, 3);
+        assert_eq!(stats.failed, 2);
+        assert_eq!(stats.last_status_code, Some(503));
+        assert_eq!(stats.last_error, Some(err.to_string()));
+
+        stats.discard(1);
+        assert_eq!(stats.dropped, 1);
+        assert_eq!(stats.delivered, 3);
+    }
+}
@@ -0,0 +1,202 @@
+use jaq_core::{
+    load::{Arena, File, Loader},
+    Compiler, Ctx, Native, RcIter,
+};
+use jaq_json::Val;
+use serde::Deserialize;
+use serde_json::Value;
+use std::{fmt, sync::Arc};
+
+/// A jq expression reshaping payloads of a destination.
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+pub struct Transform {
+    expression: Expression,
+    /// What the expression runs against.
+    #[serde(default)]
+    scope: TransformScope,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TransformScope {
+    /// The whole request body.
+    #[default]
+    Payload,
+    /// Each element of `Records` in the request body.
+    Record,
+}
+
+impl Transform {
+    /// Transform the request body. `None` means the expression dropped the body.
+    pub fn apply(&self, body: Value) -> Result<Option<Value>, String> {
+        match (self.scope, body) {
+            (TransformScope::Record, Value::Object(mut fields))
+                if fields.get("Records").is_some_and(Value::is_array) =>
+            {
+                let mut records = vec![];
+                if let Some(Value::Array(items)) = fields.remove("Records") {
+                    for item in items {
+                        records.append(&mut self.expression.run(item)?);
+                    }
+                }
+
+                if records.is_empty() {
+                    return Ok(None);
+                }
+                fields.insert("Records".into(), Value::Array(records));
+                Ok(Some(Value::Object(fields)))
+            }
+            // The whole payload, or a payload of a single record like a CloudEvents event.
+            (_, body) => {
+                let mut outputs = self.expression.run(body)?;
+                Ok(match outputs.len() {
+                    0 => None,
+                    1 => outputs.pop(),
+                    _ => Some(Value::Array(outputs)),
+                })
+            }
+        }
+    }
+}
+
+type Filter = jaq_core::Filter<Native<Val>>;
+
+/// A compiled jq expression. It supports the jq standard library.
+#[derive(Clone, Deserialize)]
+#[serde(try_from = "String")]
+struct Expression {
+    source: String,
+    filter: Arc<Filter>,
+}
+
+impl Expression {
+    fn run(&self, input: Value) -> Result<Vec<Value>, String> {
+        let inputs = RcIter::new(core::iter::empty());
+        self.filter
+            .run((Ctx::new([], &inputs), Val::from(input)))
+            .map(|output| {
+                output
+                    .map(Value::from)
+                    .map_err(|err| format!("Failed to transform the payload: {err}"))
+            })
+            .collect()
+    }
+}
+
+impl TryFrom<String> for Expression {
+    type Error = String;
+
+    fn try_from(source: String) -> Result<Self, Self::Error> {
+        let loader = Loader::new(jaq_std::defs().chain(jaq_json::defs()));
+        let arena = Arena::default();
+        let program = File {
+            code: source.as_str(),
+            path: (),
+        };
+
+        let invalid = |errors: String| format!("Invalid transform expression `{source}`: {errors}");
+
+        let modules = loader.load(&arena, program).map_err(|errs| {
+            invalid(
+                errs.iter()
+                    .map(|(_, err)| format!("{err:?}"))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            )
+        })?;
+
+        let filter = Compiler::default()
+            .with_funs(jaq_std::funs().chain(jaq_json::funs()))
+            .compile(modules)
+            .map_err(|errs| {
+                invalid(
+                    errs.iter()
+                        .flat_map(|(_, errs)| errs.iter().map(|(name, _)| name.to_string()))
+                        .map(|name| format!("undefined `{name}`"))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                )
+            })?;
+
+        Ok(Self {
+            source: source.clone(),
+            filter: Arc::new(filter),
+        })
+    }
+}
+
+impl fmt::Debug for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Expression").field(&self.source).finish()
+    }
+}
+
+impl PartialEq for Expression {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn transform(expression: &str, scope: &str) -> Transform {
+        serde_json::from_value(json!({ "expression": expression, "scope": scope })).unwrap()
+    }
+
+    fn payload() -> Value {
+        json!({
+            "Records": [
+                { "eventName": "INSERT", "dynamodb": { "NewImage": { "Name": { "S": "Joe" } } } },
+                { "eventName": "REMOVE", "dynamodb": { "OldImage": { "Name": { "S": "Ann" } } } }
+            ]
+        })
+    }
+
+    #[test]
+    fn it_reshapes_the_whole_payload() {
+        let t = transform(
+            r#"{ text: ([.Records[] | "\(.eventName) \((.dynamodb.NewImage // .dynamodb.OldImage).Name.S)"] | join("\n")) }"#,
+            "PAYLOAD",
+        );
+
+        assert_eq!(
+            t.apply(payload()).unwrap(),
+            Some(json!({ "text": "INSERT Joe\nREMOVE Ann" }))
+        );
+        assert_eq!(
+            transform("empty", "PAYLOAD").apply(payload()).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn it_reshapes_and_drops_each_record() {
+        let t = transform(
+            r#"select(.eventName != "REMOVE") | { name: .dynamodb.NewImage.Name.S, length: (.dynamodb.NewImage.Name.S | length) }"#,
+            "RECORD",
+        );
+
+        assert_eq!(
+            t.apply(payload()).unwrap(),
+            Some(json!({ "Records": [{ "name": "Joe", "length": 3 }] }))
+        );
+
+        let t = transform(r#"select(.eventName == "MODIFY")"#, "RECORD");
+        assert_eq!(t.apply(payload()).unwrap(), None);
+    }
+
+    #[test]
+    fn it_rejects_invalid_expressions_and_reports_errors() {
+        let invalid = serde_json::from_value::<Transform>(json!({ "expression": ".Records[" }));
+        assert!(invalid.is_err());
+
+        let undefined = serde_json::from_value::<Transform>(json!({ "expression": "foo(1)" }));
+        assert!(undefined.is_err());
+
+        let t = transform(".Records + 1", "PAYLOAD");
+        assert!(t.apply(payload()).is_err());
+    }
+}
@@ -0,0 +1,59 @@
+use super::{Record, RemovalReason};
+
+use serde::Deserialize;
+
+/// Which deletions by TTL a destination receives.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TtlDeletions {
+    /// Receive all the records.
+    #[default]
+    Include,
+    /// Receive all the records but deletions by TTL.
+    Exclude,
+    /// Receive only deletions by TTL.
+    Only,
+}
+
+impl TtlDeletions {
+    pub fn matches(&self, record: &Record) -> bool {
+        let is_ttl = record.removal_reason() == Some(RemovalReason::TtlExpiry);
+        match self {
+            Self::Include => true,
+            Self::Exclude => !is_ttl,
+            Self::Only => is_ttl,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn record(removal_reason: Option<&str>) -> Record {
+        serde_json::from_value(json!({
+            "eventName": if removal_reason.is_some() { "REMOVE" } else { "INSERT" },
+            "removalReason": removal_reason
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn it_matches_deletions_by_ttl() {
+        let ttl = record(Some("TTL_EXPIRY"));
+        let user = record(Some("USER_DELETE"));
+        let insert = record(None);
+
+        assert!(TtlDeletions::Include.matches(&ttl));
+        assert!(TtlDeletions::Include.matches(&user));
+
+        assert!(!TtlDeletions::Exclude.matches(&ttl));
+        assert!(TtlDeletions::Exclude.matches(&user));
+        assert!(TtlDeletions::Exclude.matches(&insert));
+
+        assert!(TtlDeletions::Only.matches(&ttl));
+        assert!(!TtlDeletions::Only.matches(&user));
+        assert!(!TtlDeletions::Only.matches(&insert));
+    }
+}
@@ -1,4 +1,5 @@
 mod config;
+mod dead_letter;
 mod error;
 mod extractor;
 mod listener;
@@ -6,13 +7,25 @@ pub mod route;
 mod state;
 mod subscription;
 
-use super::channel::{Consumer, Event, ReceiverHalf, SenderHalf, Stream};
+use super::channel::{
+    Ack, Consumer, Event, OverflowPolicy, ReceiverHalf, RestartConfig, SenderHalf, Supervisor,
+    TaskState,
+};
 use super::dynamodb::{
+    checkpoint::{CheckpointStore, FileCheckpointStore},
     client::{Client, DynamodbClient},
-    stream::{DynamodbStream, DynamodbStreamHalf},
-    types::Records,
+    dedup::{DedupConfig, DedupStore, FileDedupStore},
+    stream::{DynamodbStream, DynamodbStreamHalf, StreamStats},
+    types::{AttributeValue, Record, Records, RemovalReason, StartingPosition, StreamRecord},
+};
+use super::metrics::metrics;
+use super::{
+    ENV_CHECKPOINT_PATH, ENV_CONFIG_PATH, ENV_DEAD_LETTER_PATH, ENV_DEDUP_CAPACITY, ENV_DEDUP_PATH,
+    ENV_DEDUP_WINDOW, ENV_DYNAMODB_ENDPOINT_URL, ENV_MAX_RESTARTS, ENV_PORT, ENV_SHUTDOWN_TIMEOUT,
 };
-use super::{ENV_CONFIG_PATH, ENV_DYNAMODB_ENDPOINT_URL, ENV_PORT};
+
+use dead_letter::{DeadLetter, DeadLetterStore, FileDeadLetterStore};
+use listener::ListenerConfig;
 
 pub use config::Config;
-pub use state::{AppState, SharedState};
+pub use state::{shutdown, AppState, SharedState};
@@ -0,0 +1,198 @@
+use super::{from_guard, DeadLetter, DeadLetterStore, HttpError, Outcome, Payload, SharedState};
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{self, IntoResponse},
+    routing::{get, post},
+    Router,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::Arc;
+
+#[derive(Debug, Serialize)]
+struct Summary {
+    id: String,
+    error: String,
+    status: Option<u16>,
+    attempts: u32,
+    failed_at: DateTime<Utc>,
+    records: usize,
+}
+
+impl From<DeadLetter> for Summary {
+    fn from(letter: DeadLetter) -> Summary {
+        Summary {
+            id: letter.id,
+            error: letter.error,
+            status: letter.status,
+            attempts: letter.attempts,
+            failed_at: letter.failed_at,
+            records: letter.records,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+struct ReplayResult {
+    replayed: Vec<String>,
+    failed: Vec<String>,
+}
+
+fn store(state: &SharedState) -> Result<Arc<dyn DeadLetterStore>, HttpError> {
+    let state = state.lock().map_err(from_guard)?;
+    state
+        .dead_letters()
+        .ok_or(HttpError::NotFound("dead-letter store is disabled".into()))
+}
+
+async fn letters(
+    store: &Arc<dyn DeadLetterStore>,
+    table: &str,
+    id: &str,
+) -> Result<Vec<DeadLetter>, HttpError> {
+    Ok(store
+        .list(id)
+        .await?
+        .into_iter()
+        .filter(|l| l.table == table)
+        .collect())
+}
+
+async fn letter(
+    store: &Arc<dyn DeadLetterStore>,
+    table: &str,
+    id: &str,
+    letter_id: &str,
+) -> Result<DeadLetter, HttpError> {
+    store
+        .get(id, letter_id)
+        .await?
+        .filter(|l| l.table == table)
+        .ok_or(HttpError::NotFound(format!("dead letter {letter_id}")))
+}
+
+async fn replay(
+    state: &SharedState,
+    store: &Arc<dyn DeadLetterStore>,
+    table: &str,
+    id: &str,
+    letters: Vec<DeadLetter>,
+) -> Result<ReplayResult, HttpError> {
+    let (sink, transform) = {
+        let mut state = state.lock().map_err(from_guard)?;
+        let sink = state
+            .sink(table, id)
+            .ok_or(HttpError::NotFound(format!("destination {id}")))?;
+        (sink, state.transform(table, id))
+    };
+
+    let mut result = ReplayResult::default();
+
+    for letter in letters {
+        let payload = match Payload::replay(&letter, transform.as_ref()) {
+            Ok(Some(payload)) => payload,
+            // Nothing is left to deliver.
+            Ok(None) => {
+                result.replayed.push(letter.id);
+                continue;
+            }
+            // The transform still fails, so the letter is kept.
+            Err(_) => {
+                result.failed.push(letter.id);
+                continue;
+            }
+        };
+        match sink.deliver(&payload).await {
+            Ok(Outcome::Delivered(_)) => result.replayed.push(letter.id),
+            Ok(Outcome::Partial { .. }) | Err(_) => result.failed.push(letter.id),
+        }
+    }
+
+    // Remove only the dead letters delivered successfully.
+    store.remove(id, Some(&result.replayed)).await?;
+
+    Ok(result)
+}
+
+async fn index(
+    State(state): State<SharedState>,
+    Path((table, id)): Path<(String, String)>,
+) -> Result<impl IntoResponse, HttpError> {
+    let store = store(&state)?;
+    let summaries: Vec<Summary> = letters(&store, &table, &id)
+        .await?
+        .into_iter()
+        .map(Summary::from)
+        .collect();
+    Ok(response::Json(summaries))
+}
+
+async fn show(
+    State(state): State<SharedState>,
+    Path((table, id, letter_id)): Path<(String, String, String)>,
+) -> Result<impl IntoResponse, HttpError> {
+    let store = store(&state)?;
+    let letter = letter(&store, &table, &id, &letter_id).await?;
+    Ok(response::Json(letter))
+}
+
+async fn replay_all(
+    State(state): State<SharedState>,
+    Path((table, id)): Path<(String, String)>,
+) -> Result<impl IntoResponse, HttpError> {
+    let store = store(&state)?;
+    let letters = letters(&store, &table, &id).await?;
+    let result = replay(&state, &store, &table, &id, letters).await?;
+    Ok(response::Json(result))
+}
+
+async fn replay_one(
+    State(state): State<SharedState>,
+    Path((table, id, letter_id)): Path<(String, String, String)>,
+) -> Result<impl IntoResponse, HttpError> {
+    let store = store(&state)?;
+    let letter = letter(&store, &table, &id, &letter_id).await?;
+    let result = replay(&state, &store, &table, &id, vec![letter]).await?;
+    Ok(response::Json(result))
+}
+
+async fn purge_all(
+    State(state): State<SharedState>,
+    Path((table, id)): Path<(String, String)>,
+) -> Result<impl IntoResponse, HttpError> {
+    let store = store(&state)?;
+    let ids: Vec<String> = letters(&store, &table, &id)
+        .await?
+        .into_iter()
+        .map(|l| l.id)
+        .collect();
+    store.remove(&id, Some(&ids)).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn purge_one(
+    State(state): State<SharedState>,
+    Path((table, id, letter_id)): Path<(String, String, String)>,
+) -> Result<impl IntoResponse, HttpError> {
+    let store = store(&state)?;
+    let letter = letter(&store, &table, &id, &letter_id).await?;
+    store.remove(&id, Some(&[letter.id])).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub fn router(state: SharedState) -> Router {
+    Router::new()
+        .route("/:table/:id/dead_letters", get(index).delete(purge_all))
+        .route("/:table/:id/dead_letters/replay", post(replay_all))
+        .route(
+            "/:table/:id/dead_letters/:letter_id",
+            get(show).delete(purge_one),
+        )
+        .route(
+            "/:table/:id/dead_letters/:letter_id/replay",
+            post(replay_one),
+        )
+        .with_state(state)
+}
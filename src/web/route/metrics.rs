@@ -0,0 +1,24 @@
+use super::{from_guard, metrics, HttpError, SharedState};
+
+use axum::{extract::State, response::IntoResponse, routing::get, Router};
+
+async fn index(State(state): State<SharedState>) -> Result<impl IntoResponse, HttpError> {
+    {
+        let state = state.lock().map_err(from_guard)?;
+        metrics()
+            .subscriptions
+            .set(state.subscription_count() as i64);
+        metrics().listeners.set(state.listener_count() as i64);
+    }
+
+    let body = metrics().encode().map_err(anyhow::Error::from)?;
+    let header = [("content-type", "text/plain; version=0.0.4")];
+    Ok((header, body))
+}
+
+/// Mounted under `/_`, which can't be a table name, so that it doesn't shadow `GET /:table`.
+pub fn router(state: SharedState) -> Router {
+    Router::new()
+        .route("/_/metrics", get(index))
+        .with_state(state)
+}
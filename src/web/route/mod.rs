@@ -1,9 +1,14 @@
+mod dead_letter;
+mod metrics;
 pub mod root;
 
 use super::{
+    config::Entry,
+    dead_letter::{DeadLetter, DeadLetterStore},
     error::HttpError,
     extractor::{FromValidate, Json},
-    SharedState,
+    listener::{Outcome, Payload, SinkConfig},
+    metrics, ListenerConfig, SharedState, StartingPosition,
 };
 
 use std::sync::{MutexGuard, PoisonError};
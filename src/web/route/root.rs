@@ -1,4 +1,7 @@
-use super::{from_guard, FromValidate, HttpError, Json, SharedState};
+use super::{
+    dead_letter, from_guard, metrics, Entry, FromValidate, HttpError, Json, ListenerConfig,
+    SharedState, SinkConfig, StartingPosition,
+};
 
 use axum::{
     extract::{Path, State},
@@ -8,20 +11,30 @@ use axum::{
     Router,
 };
 use serde::Deserialize;
-use validator::Validate;
+use validator::{Validate, ValidationError};
 
 #[derive(Debug, Deserialize, Validate)]
+#[validate(schema(function = "validate_destination"))]
 struct RawEntryBody {
     #[validate(required, length(max = 255))]
     table_name: Option<String>,
-    #[validate(required, length(max = 255))]
+    #[validate(length(min = 1, max = 255))]
+    id: Option<String>,
+    #[validate(length(max = 255))]
     url: Option<String>,
+    sink: Option<SinkConfig>,
+    starting_position: Option<StartingPosition>,
+    #[serde(flatten)]
+    listener: ListenerConfig,
 }
 
 #[derive(Debug)]
 struct EntryBody {
     table_name: String,
-    url: String,
+    id: Option<String>,
+    sink: SinkConfig,
+    starting_position: StartingPosition,
+    listener: ListenerConfig,
 }
 
 impl FromValidate for EntryBody {
@@ -30,24 +43,71 @@ impl FromValidate for EntryBody {
     fn from(b: RawEntryBody) -> EntryBody {
         EntryBody {
             table_name: b.table_name.expect("`table_name` should be Some"),
-            url: b.url.expect("`url` should be Some"),
+            id: b.id,
+            sink: SinkConfig::resolve(b.url, b.sink).expect("the destination should be valid"),
+            starting_position: b.starting_position.unwrap_or_default(),
+            listener: b.listener,
         }
     }
 }
 
+fn validate_destination(body: &RawEntryBody) -> Result<(), ValidationError> {
+    SinkConfig::resolve(body.url.clone(), body.sink.clone())
+        .map(|_| ())
+        .map_err(|message| {
+            let mut err = ValidationError::new("destination");
+            err.message = Some(message.into());
+            err
+        })
+}
+
 async fn index(State(state): State<SharedState>) -> Result<impl IntoResponse, HttpError> {
     let state = state.lock().map_err(from_guard)?;
     Ok(response::Json(state.serialize()))
 }
 
+async fn show_table(
+    State(state): State<SharedState>,
+    Path(table): Path<String>,
+) -> Result<impl IntoResponse, HttpError> {
+    let state = state.lock().map_err(from_guard)?;
+    let status = state
+        .status(&table)
+        .ok_or_else(|| HttpError::NotFound(table))?;
+    Ok(response::Json(status))
+}
+
+async fn show_destination(
+    State(state): State<SharedState>,
+    Path((table, id)): Path<(String, String)>,
+) -> Result<impl IntoResponse, HttpError> {
+    let state = state.lock().map_err(from_guard)?;
+    let dest = state
+        .destination(&table, &id)
+        .ok_or_else(|| HttpError::NotFound(format!("{table}/{id}")))?;
+    Ok(response::Json(dest))
+}
+
 async fn register(
     State(state): State<SharedState>,
     Json(body): Json<EntryBody>,
 ) -> Result<impl IntoResponse, HttpError> {
-    let EntryBody { table_name, url } = body;
+    let EntryBody {
+        table_name,
+        id,
+        sink,
+        starting_position,
+        listener,
+    } = body;
 
     let mut state = state.lock().map_err(from_guard)?;
-    let dest = state.add_sub(table_name, url);
+    let dest = state.add_sub(Entry {
+        table_name,
+        id,
+        sink,
+        starting_position,
+        listener,
+    });
 
     Ok(response::Json(dest))
 }
@@ -72,9 +132,13 @@ async fn unsubscribe_table(
 
 pub fn router(state: SharedState) -> Router {
     Router::new()
+        .route("/:table/:id", get(show_destination))
         .route("/:table/:id", delete(deregister_url))
+        .route("/:table", get(show_table))
         .route("/:table", delete(unsubscribe_table))
         .route("/", get(index))
         .route("/", post(register))
-        .with_state(state)
+        .with_state(state.clone())
+        .merge(dead_letter::router(state.clone()))
+        .merge(metrics::router(state))
 }
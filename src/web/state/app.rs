@@ -1,10 +1,19 @@
-use super::{Config, Destination, DynamodbClient, Subscription};
+use super::{
+    CheckpointStore, Config, DeadLetterStore, DedupConfig, DedupStore, Destination, DynamodbClient,
+    Entry, FileCheckpointStore, FileDeadLetterStore, FileDedupStore, RestartConfig, Sink,
+    Subscription, SubscriptionStatus, Transform,
+};
 
 use std::{collections::HashMap, sync::Arc};
+use tracing::warn;
 
-#[derive(Debug)]
 pub struct AppState {
     client: DynamodbClient,
+    checkpoint_store: Option<Arc<dyn CheckpointStore>>,
+    dead_letters: Option<Arc<dyn DeadLetterStore>>,
+    restart: RestartConfig,
+    dedup: Option<DedupConfig>,
+    dedup_store: Option<Arc<dyn DedupStore>>,
     subscriptions: Vec<Subscription>,
 }
 
@@ -15,13 +24,34 @@ impl AppState {
             .endpoint_url(config.endpoint_url())
             .build();
 
+        let checkpoint_store = match config.checkpoint_path() {
+            Some(path) => open_checkpoint_store(path).await,
+            None => None,
+        };
+
+        let dead_letters = match config.dead_letter_path() {
+            Some(path) => open_dead_letter_store(path).await,
+            None => None,
+        };
+
+        let dedup = config.dedup();
+        let dedup_store = match (dedup.as_ref(), config.dedup_path()) {
+            (Some(_), Some(path)) => open_dedup_store(path).await,
+            _ => None,
+        };
+
         let mut state = Self {
             client,
+            checkpoint_store,
+            dead_letters,
+            restart: config.restart(),
+            dedup,
+            dedup_store,
             subscriptions: vec![],
         };
 
         for entry in config.entries() {
-            state.add_sub(entry.table_name, entry.url);
+            state.add_sub(entry);
         }
 
         state
@@ -31,30 +61,81 @@ impl AppState {
         Arc::new(self.client.clone())
     }
 
-    pub fn serialize(&self) -> HashMap<String, Vec<Destination>> {
+    pub fn dead_letters(&self) -> Option<Arc<dyn DeadLetterStore>> {
+        self.dead_letters.clone()
+    }
+
+    pub fn sink(&mut self, table: &str, id: &str) -> Option<Arc<dyn Sink>> {
+        self.sub(table).and_then(|sub| sub.sink(id))
+    }
+
+    pub fn transform(&mut self, table: &str, id: &str) -> Option<Transform> {
+        self.sub(table).and_then(|sub| sub.transform(id))
+    }
+
+    pub fn serialize(&self) -> HashMap<String, SubscriptionStatus> {
         self.subscriptions
             .iter()
             .fold(HashMap::new(), |mut acc, sub| {
-                let (table, destinations) = sub.serialize();
-                acc.insert(table, destinations);
+                acc.insert(sub.table().to_string(), sub.status());
                 acc
             })
     }
 
-    pub fn add_sub(&mut self, table: String, url: String) -> Destination {
-        let url = url.as_str();
+    pub fn subscription_count(&self) -> usize {
+        self.subscriptions.len()
+    }
+
+    pub fn listener_count(&self) -> usize {
+        self.subscriptions.iter().map(|s| s.listener_count()).sum()
+    }
+
+    /// Get the runtime status of the table's subscription.
+    pub fn status(&self, table: &str) -> Option<SubscriptionStatus> {
+        self.subscriptions
+            .iter()
+            .find(|s| s.table() == table)
+            .map(|sub| sub.status())
+    }
+
+    /// Get the destination of the table with its runtime status.
+    pub fn destination(&self, table: &str, id: &str) -> Option<Destination> {
+        self.subscriptions
+            .iter()
+            .find(|s| s.table() == table)
+            .and_then(|sub| sub.destination(id))
+    }
+
+    /// Add a destination to the table's subscription. The starting position only takes effect
+    /// when the subscription is newly created.
+    pub fn add_sub(&mut self, entry: Entry) -> Destination {
+        let Entry {
+            table_name: table,
+            id,
+            sink,
+            starting_position,
+            listener,
+        } = entry;
 
         self.sub(&table)
             .as_mut()
-            .map(|sub| Destination::from(sub.set_listener(url)))
+            .map(|sub| {
+                Destination::from(sub.set_listener(id.clone(), sink.clone(), listener.clone()))
+            })
             .unwrap_or_else(|| {
                 let client = Arc::new(self.client.clone());
 
                 let mut sub = Subscription::builder()
                     .set_client(client)
                     .set_table(&table)
+                    .set_starting_position(starting_position)
+                    .set_checkpoint_store(self.checkpoint_store.clone())
+                    .set_dead_letter_store(self.dead_letters.clone())
+                    .set_restart_config(self.restart.clone())
+                    .set_dedup(self.dedup.clone())
+                    .set_dedup_store(self.dedup_store.clone())
                     .build();
-                let dest = sub.set_listener(url);
+                let dest = sub.set_listener(id, sink, listener);
 
                 self.subscriptions.push(sub);
 
@@ -62,12 +143,24 @@ impl AppState {
             })
     }
 
+    /// Remove the destination. The subscription is removed with its last destination so that
+    /// a destination added later starts a new stream.
     pub fn remove_listener(&mut self, table: String, id: String) {
-        if let Some(sub) = self.sub(&table).as_mut() {
+        let is_empty = self.sub(&table).is_some_and(|sub| {
             sub.unset_listener(id);
+            sub.listener_count() == 0
+        });
+
+        if is_empty {
+            self.remove_sub(table);
         }
     }
 
+    /// Take all subscriptions out to shut them down.
+    pub fn take_subs(&mut self) -> Vec<Subscription> {
+        self.subscriptions.drain(..).collect()
+    }
+
     pub fn remove_sub(&mut self, table: String) {
         self.subscriptions.retain(|s| s.table() != table.as_str());
     }
@@ -80,3 +173,36 @@ impl AppState {
         self.subscriptions.iter_mut().find(|s| s.table() == table)
     }
 }
+
+async fn open_checkpoint_store(path: String) -> Option<Arc<dyn CheckpointStore>> {
+    match FileCheckpointStore::open(&path).await {
+        Ok(store) => Some(Arc::new(store)),
+        Err(err) => {
+            warn!("Failed to open checkpoint file: {path}. {err}");
+            warn!("Skip checkpointing.");
+            None
+        }
+    }
+}
+
+async fn open_dead_letter_store(path: String) -> Option<Arc<dyn DeadLetterStore>> {
+    match FileDeadLetterStore::open(&path).await {
+        Ok(store) => Some(Arc::new(store)),
+        Err(err) => {
+            warn!("Failed to open dead-letter file: {path}. {err}");
+            warn!("Skip keeping dead letters.");
+            None
+        }
+    }
+}
+
+async fn open_dedup_store(path: String) -> Option<Arc<dyn DedupStore>> {
+    match FileDedupStore::open(&path).await {
+        Ok(store) => Some(Arc::new(store)),
+        Err(err) => {
+            warn!("Failed to open dedup file: {path}. {err}");
+            warn!("Skip persisting event IDs for deduplication.");
+            None
+        }
+    }
+}
@@ -1,12 +1,19 @@
 mod app;
 
 use super::{
-    config::Config,
-    subscription::{Destination, Subscription},
-    DynamodbClient,
+    config::{Config, Entry},
+    listener::{Sink, Transform},
+    subscription::{Destination, Subscription, SubscriptionStatus},
+    CheckpointStore, DeadLetterStore, DedupConfig, DedupStore, DynamodbClient, FileCheckpointStore,
+    FileDeadLetterStore, FileDedupStore, RestartConfig,
 };
 
-use std::sync::{Arc, Mutex};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::time::Instant;
+use tracing::{error, info};
 
 pub use app::AppState;
 
@@ -17,3 +24,26 @@ impl From<AppState> for SharedState {
         Arc::new(Mutex::new(state))
     }
 }
+
+/// Stop all subscriptions gracefully within the timeout.
+pub async fn shutdown(state: SharedState, timeout: Duration) {
+    let subs = match state.lock() {
+        Ok(mut state) => state.take_subs(),
+        Err(err) => {
+            error!("Failed to lock state. Skip shutting down subscriptions: {err}");
+            return;
+        }
+    };
+
+    info!("Shutting down {} subscription(s).", subs.len());
+
+    let deadline = Instant::now() + timeout;
+    let tasks: Vec<_> = subs
+        .into_iter()
+        .map(|sub| tokio::spawn(sub.shutdown(deadline)))
+        .collect();
+
+    for task in tasks {
+        let _ = task.await;
+    }
+}
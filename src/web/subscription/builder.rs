@@ -1,4 +1,6 @@
-use super::super::{Client, DynamodbStream, Stream};
+use super::super::{
+    CheckpointStore, Client, DedupConfig, DedupStore, DynamodbStream, StartingPosition,
+};
 use super::*;
 
 use std::sync::Arc;
@@ -7,6 +9,12 @@ use std::sync::Arc;
 pub struct SubscriptionBuilder {
     client: Option<Arc<dyn Client>>,
     table: Option<String>,
+    position: StartingPosition,
+    checkpoint_store: Option<Arc<dyn CheckpointStore>>,
+    dead_letters: Option<Arc<dyn DeadLetterStore>>,
+    restart: RestartConfig,
+    dedup: Option<DedupConfig>,
+    dedup_store: Option<Arc<dyn DedupStore>>,
 }
 
 impl SubscriptionBuilder {
@@ -28,6 +36,39 @@ impl SubscriptionBuilder {
         }
     }
 
+    pub fn set_starting_position(self, position: StartingPosition) -> Self {
+        Self { position, ..self }
+    }
+
+    pub fn set_checkpoint_store(self, store: Option<Arc<dyn CheckpointStore>>) -> Self {
+        Self {
+            checkpoint_store: store,
+            ..self
+        }
+    }
+
+    pub fn set_dead_letter_store(self, store: Option<Arc<dyn DeadLetterStore>>) -> Self {
+        Self {
+            dead_letters: store,
+            ..self
+        }
+    }
+
+    pub fn set_restart_config(self, restart: RestartConfig) -> Self {
+        Self { restart, ..self }
+    }
+
+    pub fn set_dedup(self, dedup: Option<DedupConfig>) -> Self {
+        Self { dedup, ..self }
+    }
+
+    pub fn set_dedup_store(self, store: Option<Arc<dyn DedupStore>>) -> Self {
+        Self {
+            dedup_store: store,
+            ..self
+        }
+    }
+
     pub fn build(self) -> Subscription {
         assert!(self.client.is_some(), "\"client\" is not set");
         assert!(self.table.is_some(), "\"table\" is not set");
@@ -38,17 +79,31 @@ impl SubscriptionBuilder {
         let (mut stream, stream_half) = DynamodbStream::builder()
             .set_client(client)
             .set_table(&table)
+            .set_starting_position(self.position)
+            .set_checkpoint_store(self.checkpoint_store)
+            .set_dedup(self.dedup)
+            .set_dedup_store(self.dedup_store)
             .build();
 
-        tokio::spawn(async move {
-            stream.start_streaming(Some(3)).await;
+        let stream_supervisor = Supervisor::new(self.restart.clone());
+        let supervisor = stream_supervisor.clone();
+        let stream_task = tokio::spawn(async move {
+            supervisor.supervise_stream(&mut stream, Some(3)).await;
         });
 
         Subscription {
             table,
             destinations: HashMap::new(),
+            sinks: HashMap::new(),
+            transforms: HashMap::new(),
+            dead_letters: self.dead_letters,
+            restart: self.restart,
+            stream_supervisor,
             stream_half,
+            stream_task,
             listener_halfs: HashMap::new(),
+            listener_supervisors: HashMap::new(),
+            listener_tasks: HashMap::new(),
         }
     }
 }
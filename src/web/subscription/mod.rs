@@ -1,22 +1,37 @@
 mod builder;
 
 use super::{
-    listener::{Listener, ListenerHalf},
-    Consumer, DynamodbStreamHalf,
+    listener::{
+        DeliveryStats, Listener, ListenerConfig, ListenerHalf, Sink, SinkConfig, Transform,
+    },
+    DeadLetterStore, DynamodbStreamHalf, Event, RestartConfig, SenderHalf, StreamStats, Supervisor,
+    TaskState,
 };
 
 use serde::Serialize;
-use std::collections::HashMap;
-use ulid::Ulid;
+use sha2::{Digest, Sha256};
+use std::{collections::HashMap, sync::Arc};
+use tokio::{
+    task::JoinHandle,
+    time::{timeout_at, Instant},
+};
+use tracing::{info, warn};
 
 pub use builder::SubscriptionBuilder;
 
-#[derive(Debug)]
 pub struct Subscription {
     table: String,
-    destinations: HashMap<String, String>,
+    destinations: HashMap<String, SinkConfig>,
+    sinks: HashMap<String, Arc<dyn Sink>>,
+    transforms: HashMap<String, Transform>,
+    dead_letters: Option<Arc<dyn DeadLetterStore>>,
+    restart: RestartConfig,
+    stream_supervisor: Supervisor,
     stream_half: DynamodbStreamHalf,
+    stream_task: JoinHandle<()>,
     listener_halfs: HashMap<String, ListenerHalf>,
+    listener_supervisors: HashMap<String, Supervisor>,
+    listener_tasks: HashMap<String, JoinHandle<()>>,
 }
 
 impl Subscription {
@@ -28,26 +43,103 @@ impl Subscription {
         self.table.as_str()
     }
 
-    pub fn serialize(&self) -> (String, Vec<Destination>) {
-        (
-            self.table.clone(),
-            Destination::from_hashmap(self.destinations.clone()),
-        )
+    pub fn status(&self) -> SubscriptionStatus {
+        let task = self.stream_supervisor.status();
+        let destinations = self
+            .destinations
+            .keys()
+            .filter_map(|id| self.destination(id))
+            .collect();
+
+        SubscriptionStatus {
+            state: task.state,
+            restarts: task.restarts,
+            last_error: task.last_error,
+            stats: self.stream_half.stats(),
+            destinations,
+        }
+    }
+
+    pub fn listener_count(&self) -> usize {
+        self.listener_halfs.len()
     }
 
-    pub fn set_listener<T: Into<String>>(&mut self, url: T) -> (String, String) {
-        let url: String = url.into();
-        let id = Ulid::new().to_string();
+    /// Get the destination with its runtime status.
+    pub fn destination(&self, id: &str) -> Option<Destination> {
+        let sink = self.destinations.get(id)?;
+
+        let status = match (
+            self.listener_supervisors.get(id),
+            self.listener_halfs.get(id),
+        ) {
+            (Some(supervisor), Some(half)) => {
+                let task = supervisor.status();
+                let mut stats = half.stats();
+                stats.last_error = stats.last_error.or(task.last_error);
+
+                Some(DestinationStatus {
+                    state: task.state,
+                    restarts: task.restarts,
+                    stats,
+                })
+            }
+            _ => None,
+        };
+
+        Some(Destination {
+            id: id.into(),
+            sink: sink.clone(),
+            status,
+        })
+    }
+
+    /// Get the sink of the destination to send records directly, like replaying dead letters.
+    pub fn sink(&self, id: &str) -> Option<Arc<dyn Sink>> {
+        self.sinks.get(id).cloned()
+    }
+
+    /// Get the transform of the destination to replay the dead letters it failed on.
+    pub fn transform(&self, id: &str) -> Option<Transform> {
+        self.transforms.get(id).cloned()
+    }
+
+    /// Add a destination. A destination whose ID already exists is kept as it is.
+    pub fn set_listener(
+        &mut self,
+        id: Option<String>,
+        sink: SinkConfig,
+        config: ListenerConfig,
+    ) -> (String, SinkConfig) {
+        let id = id.unwrap_or_else(|| self.derive_id(&sink));
 
         if !self.has_dest(&id) {
-            self.add_dest(&id, &url);
+            self.add_dest(&id, &sink);
         }
 
         if !self.has_listener(&id) {
-            self.add_listener(&id, &url);
+            self.add_listener(&id, &sink, config);
         }
 
-        (id, url)
+        let sink = self.destinations.get(&id).cloned().unwrap_or(sink);
+        (id, sink)
+    }
+
+    /// Derive the ID of a destination from the table and the sink, so that the destination keeps
+    /// its ID, and its dead letters, across restarts. Destinations with the same sink are
+    /// numbered in the order they are added.
+    fn derive_id(&self, sink: &SinkConfig) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.table.as_bytes());
+        hasher.update(serde_json::to_vec(sink).unwrap_or_default());
+        let hash = hex::encode(&hasher.finalize()[..8]);
+
+        (1..)
+            .map(|n| match n {
+                1 => hash.clone(),
+                n => format!("{hash}-{n}"),
+            })
+            .find(|id| !self.has_dest(id))
+            .unwrap_or(hash)
     }
 
     pub fn unset_listener<T: Into<String>>(&mut self, id: T) {
@@ -60,8 +152,8 @@ impl Subscription {
         self.destinations.contains_key(id)
     }
 
-    fn add_dest(&mut self, id: &str, url: &str) {
-        self.destinations.insert(id.into(), url.into());
+    fn add_dest(&mut self, id: &str, sink: &SinkConfig) {
+        self.destinations.insert(id.into(), sink.clone());
     }
 
     fn remove_dest(&mut self, id: &str) {
@@ -72,41 +164,133 @@ impl Subscription {
         self.listener_halfs.contains_key(id)
     }
 
-    fn add_listener(&mut self, id: &str, url: &str) {
-        let receiver = self.stream_half.receiver();
+    fn add_listener(&mut self, id: &str, sink: &SinkConfig, config: ListenerConfig) {
+        let receiver = self
+            .stream_half
+            .receiver(id, config.buffer_size, config.overflow_policy);
+        let sink = sink.build(&self.table, config.retry);
         let (mut listener, listener_half) = Listener::builder()
-            .set_url(url)
+            .set_id(id)
+            .set_table(&self.table)
+            .set_sink(Arc::clone(&sink))
+            .set_filter(config.filters)
+            .set_ttl_deletions(config.ttl)
+            .set_projection(config.projection)
+            .set_format(config.format)
+            .set_batch_config(config.batch)
+            .set_transform(config.transform.clone())
+            .set_diff(config.diff)
+            .set_dead_letter_store(self.dead_letters.clone())
             .set_records_receiver(receiver)
             .build();
 
-        tokio::spawn(async move {
-            listener.start_consuming().await;
+        let listener_supervisor = Supervisor::new(self.restart.clone());
+        let supervisor = listener_supervisor.clone();
+        let task = tokio::spawn(async move {
+            supervisor.supervise_consumer(&mut listener).await;
         });
 
         self.listener_halfs.insert(id.into(), listener_half);
+        self.listener_tasks.insert(id.into(), task);
+        self.listener_supervisors
+            .insert(id.into(), listener_supervisor);
+        self.sinks.insert(id.into(), sink);
+        if let Some(transform) = config.transform {
+            self.transforms.insert(id.into(), transform);
+        }
     }
 
     fn remove_listener(&mut self, id: &str) {
         // When the listener_half drops, the associated listener will also
         // drop due to the listener_half's drop trait.
         self.listener_halfs.remove(id);
+        self.listener_supervisors.remove(id);
+        self.listener_tasks.remove(id);
+        self.sinks.remove(id);
+        self.transforms.remove(id);
+        self.stream_half.remove_receiver(id);
+    }
+
+    /// Stop the stream and the listeners gracefully. Tasks still running at the deadline are
+    /// aborted.
+    pub async fn shutdown(self, deadline: Instant) {
+        let Subscription {
+            table,
+            mut stream_half,
+            stream_task,
+            mut listener_halfs,
+            listener_tasks,
+            ..
+        } = self;
+
+        // 1. Stop the stream. It waits for the listeners to deliver the records it has sent and
+        //    commits their checkpoints before stopping.
+        stream_half.send_event(Event::Close);
+        wait(
+            format!("stream of \"{table}\" table"),
+            stream_task,
+            deadline,
+        )
+        .await;
+
+        // 2. Close the records channels so that idle listeners stop.
+        drop(stream_half);
+
+        // 3. Stop the listeners. They finish in-flight deliveries and deliver buffered records.
+        for half in listener_halfs.values_mut() {
+            half.send_event(Event::Close);
+        }
+        for (id, task) in listener_tasks {
+            wait(format!("listener \"{id}\""), task, deadline).await;
+        }
+    }
+}
+
+async fn wait(name: String, mut task: JoinHandle<()>, deadline: Instant) {
+    match timeout_at(deadline, &mut task).await {
+        Ok(_) => info!("The {name} has stopped."),
+        Err(_) => {
+            warn!("The {name} didn't stop until the deadline. Abort it.");
+            task.abort();
+        }
     }
 }
 
+/// The runtime status of the table's stream and its destinations.
+#[derive(Debug, Clone, Serialize)]
+pub struct SubscriptionStatus {
+    state: TaskState,
+    restarts: u32,
+    last_error: Option<String>,
+    #[serde(flatten)]
+    stats: StreamStats,
+    destinations: Vec<Destination>,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct Destination {
     id: String,
-    url: String,
+    #[serde(flatten)]
+    sink: SinkConfig,
+    #[serde(flatten)]
+    status: Option<DestinationStatus>,
 }
 
-impl From<(String, String)> for Destination {
-    fn from((id, url): (String, String)) -> Self {
-        Self { id, url }
-    }
+/// The runtime status of the listener delivering records to the destination.
+#[derive(Debug, Clone, Serialize)]
+pub struct DestinationStatus {
+    state: TaskState,
+    restarts: u32,
+    #[serde(flatten)]
+    stats: DeliveryStats,
 }
 
-impl Destination {
-    fn from_hashmap(map: HashMap<String, String>) -> Vec<Self> {
-        map.into_iter().map(Destination::from).collect()
+impl From<(String, SinkConfig)> for Destination {
+    fn from((id, sink): (String, SinkConfig)) -> Self {
+        Self {
+            id,
+            sink,
+            status: None,
+        }
     }
 }